@@ -0,0 +1,161 @@
+//! Benchmarks of every generator, part and variant on generated inputs of
+//! growing size. Run with `cargo bench`, or e.g. `cargo bench -- Day6` for a
+//! single day.
+
+use std::hint::black_box;
+
+use aoc2024::{
+    day1::Day1,
+    day2::{Day2, SafetyPolicy},
+    day3::Day3,
+    day4::Day4,
+    day5::Day5,
+    day6::Day6,
+    gen,
+    solution::{Part, Solution},
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Input sizes in bytes, the real inputs are around 20KB.
+const SIZES: &[usize] = &[1_000, 10_000, 100_000];
+
+/// Generated guard walks cover a fixed share of the map, so the brute force
+/// part 2, walking the whole path again for every cell on it, is quadratic in
+/// the map size. Keep its maps smaller.
+const DAY6_SIZES: &[usize] = &[1_000, 4_000, 16_000, 64_000];
+
+const SEED: u64 = 2024;
+
+fn bench_day<S: Solution>(c: &mut Criterion, sizes: &[usize]) {
+    let inputs: Vec<(usize, String)> = sizes
+        .iter()
+        .map(|&bytes| {
+            let input = gen::generate(S::DAY, SEED, bytes).expect("day has a generator");
+            (bytes, input)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group(format!("Day{} - generator", S::DAY));
+    for (bytes, input) in &inputs {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(bytes), input, |b, input| {
+            b.iter(|| S::parse(black_box(input)).unwrap())
+        });
+    }
+    group.finish();
+
+    let parsed: Vec<(usize, u64, S::Input)> = inputs
+        .iter()
+        .map(|(bytes, input)| (*bytes, input.len() as u64, S::parse(input).unwrap()))
+        .collect();
+
+    for part in Part::ALL {
+        let default: fn(&S::Input) -> _ = match part {
+            Part::One => S::part1,
+            Part::Two => S::part2,
+        };
+        let runners = std::iter::once(("(default)", default)).chain(
+            S::variants()
+                .into_iter()
+                .filter(|v| v.part == part)
+                .map(|v| (v.name, v.run)),
+        );
+
+        for (name, run) in runners {
+            let mut group =
+                c.benchmark_group(format!("Day{} - Part{}/{}", S::DAY, part.number(), name));
+            for (bytes, len, input) in &parsed {
+                group.throughput(Throughput::Bytes(*len));
+                group.bench_with_input(BenchmarkId::from_parameter(bytes), input, |b, input| {
+                    b.iter(|| run(black_box(input)).unwrap())
+                });
+            }
+            group.finish();
+        }
+    }
+}
+
+fn day1(c: &mut Criterion) {
+    bench_day::<Day1>(c, SIZES);
+}
+
+fn day2(c: &mut Criterion) {
+    bench_day::<Day2>(c, SIZES);
+}
+
+/// Part 2 before the dampener, rebuilding every report without each level
+/// in turn.
+fn quadratic_accepts(report: &[i32]) -> bool {
+    let valid = |report: &[i32]| {
+        let monotonic = report.is_sorted() || report.is_sorted_by(|a, b| a >= b);
+        monotonic
+            && report
+                .windows(2)
+                .all(|pair| (1..=3).contains(&pair[0].abs_diff(pair[1])))
+    };
+
+    valid(report)
+        || (0..report.len()).any(|i| {
+            let damped: Vec<i32> = report
+                .iter()
+                .enumerate()
+                .filter_map(|(j, &level)| (j != i).then_some(level))
+                .collect();
+            valid(&damped)
+        })
+}
+
+/// The dampener against the quadratic search it replaced.
+fn day2_dampener(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Day2 - dampener");
+    for &bytes in SIZES {
+        let input = gen::generate(2, SEED, bytes).expect("day has a generator");
+        let reports = Day2::parse(&input).unwrap();
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("policy", bytes), &reports, |b, reports| {
+            b.iter(|| {
+                black_box(reports)
+                    .iter()
+                    .filter(|report| SafetyPolicy::DAMPENED.accepts(report))
+                    .count()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("quadratic", bytes),
+            &reports,
+            |b, reports| {
+                b.iter(|| {
+                    black_box(reports)
+                        .iter()
+                        .filter(|report| quadratic_accepts(report))
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn day3(c: &mut Criterion) {
+    bench_day::<Day3>(c, SIZES);
+}
+
+fn day4(c: &mut Criterion) {
+    bench_day::<Day4>(c, SIZES);
+}
+
+fn day5(c: &mut Criterion) {
+    bench_day::<Day5>(c, SIZES);
+}
+
+fn day6(c: &mut Criterion) {
+    bench_day::<Day6>(c, DAY6_SIZES);
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = day1, day2, day2_dampener, day3, day4, day5, day6
+}
+criterion_main!(benches);
@@ -0,0 +1,215 @@
+use std::{collections::BTreeMap, error::Error, fmt};
+
+use crate::solution::{Answer, Part, Runner};
+
+/// Expected answers, read from a TOML subset:
+///
+/// ```toml
+/// [day1]
+/// part1 = 1234
+/// part2 = 5678
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    answers: BTreeMap<(u8, Part), Answer>,
+}
+
+/// Malformed answers file, `line` is 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswersError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "answers line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AnswersError {}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers, AnswersError> {
+        let mut answers = BTreeMap::new();
+        let mut day = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: String| AnswersError {
+                line: i + 1,
+                message,
+            };
+
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let n = section
+                    .trim()
+                    .strip_prefix("day")
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .ok_or_else(|| error(format!("invalid section `{}`", section)))?;
+                day = Some(n);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `key = value`, got `{}`", line)))?;
+            let day = day.ok_or_else(|| error("answer outside of a [dayN] section".into()))?;
+            let part = match key.trim() {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                other => return Err(error(format!("unknown key `{}`", other))),
+            };
+            let value = value.trim().trim_matches('"');
+            let answer = value
+                .parse::<Answer>()
+                .map_err(|_| error(format!("invalid answer `{}`", value)))?;
+
+            answers.insert((day, part), answer);
+        }
+
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<Answer> {
+        self.answers.get(&(day, part)).copied()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Wrong answer, or the solution failed with the given error.
+    Fail,
+    /// No expected answer or no input to check against.
+    Missing,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Pass => "pass",
+            Status::Fail => "FAIL",
+            Status::Missing => "missing",
+        })
+    }
+}
+
+/// Outcome of checking one part or variant against its expected answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub variant: Option<&'static str>,
+    pub expected: Option<Answer>,
+    /// Answer, or the error message of a failed run.
+    pub actual: Result<Answer, String>,
+    pub status: Status,
+}
+
+/// Run every part and variant of `runner` on `input` and compare with
+/// `answers`. Without an input every check is [`Status::Missing`].
+pub fn verify(runner: &dyn Runner, input: Option<&str>, answers: &Answers) -> Vec<Check> {
+    let mut checks = vec![];
+
+    for part in Part::ALL {
+        let expected = answers.get(runner.day(), part);
+        let variants = std::iter::once(None).chain(runner.variants(part).into_iter().map(Some));
+
+        for variant in variants {
+            let actual = match input {
+                Some(input) => runner
+                    .run(input, part, variant)
+                    .map(|run| run.answer)
+                    .map_err(|err| err.to_string()),
+                None => Err("no input".to_string()),
+            };
+
+            let status = match (&actual, expected) {
+                (Err(_), _) if input.is_none() => Status::Missing,
+                (Err(_), _) => Status::Fail,
+                (Ok(_), None) => Status::Missing,
+                (Ok(answer), Some(expected)) if *answer == expected => Status::Pass,
+                (Ok(_), Some(_)) => Status::Fail,
+            };
+
+            checks.push(Check {
+                day: runner.day(),
+                part,
+                variant,
+                expected,
+                actual,
+                status,
+            });
+        }
+    }
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::answers::*;
+    use crate::inputs::Inputs;
+    use crate::solution;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# personal input
+[day1]
+part1 = 11
+part2 = \"31\" # quoted
+
+[day6]
+part2 = 6",
+        )
+        .unwrap();
+
+        assert_eq!(Some(11), answers.get(1, Part::One));
+        assert_eq!(Some(31), answers.get(1, Part::Two));
+        assert_eq!(None, answers.get(6, Part::One));
+        assert_eq!(Some(6), answers.get(6, Part::Two));
+
+        assert_eq!(
+            Err(AnswersError {
+                line: 1,
+                message: "answer outside of a [dayN] section".to_string()
+            }),
+            Answers::parse("part1 = 1")
+        );
+        assert_eq!(2, Answers::parse("[day1]\npart3 = 1").unwrap_err().line);
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse("[day1]\npart1 = 11\npart2 = 30").unwrap();
+        let input = &Inputs::fixtures().load(1, "example").unwrap();
+        let day1 = solution::day(1).unwrap();
+
+        let statuses: Vec<(Part, Option<&str>, Status)> = verify(day1, Some(input), &answers)
+            .into_iter()
+            .map(|check| (check.part, check.variant, check.status))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (Part::One, None, Status::Pass),
+                (Part::One, Some("heap"), Status::Pass),
+                (Part::One, Some("radix"), Status::Pass),
+                (Part::Two, None, Status::Fail),
+                (Part::Two, Some("merge"), Status::Fail),
+                (Part::Two, Some("frequency"), Status::Fail),
+            ],
+            statuses
+        );
+
+        assert!(verify(day1, None, &answers)
+            .iter()
+            .all(|check| check.status == Status::Missing));
+    }
+}
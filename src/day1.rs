@@ -1,84 +1,384 @@
+use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::error::{ParseError, SolveError};
+use crate::parse::words;
+use crate::solution::{Answer, Part, Solution, Variant};
+
 #[derive(Debug, Clone)]
 pub struct Day1Input {
     left: Vec<i64>,
     right: Vec<i64>,
 }
 
+/// Flat frequency arrays are used for part 2 when the right list spans at
+/// most this many values.
+const MAX_FREQUENCY_RANGE: i64 = 1 << 22;
+
 #[aoc_generator(day1)]
-fn parse_input(input: &str) -> Day1Input {
-    let mut left = vec![];
-    let mut right = vec![];
+fn parse_input(input: &str) -> Result<Day1Input, ParseError> {
+    // lines are about 14 bytes in the real input
+    let mut left = Vec::with_capacity(input.len() / 14 + 1);
+    let mut right = Vec::with_capacity(input.len() / 14 + 1);
 
-    input.lines().for_each(|line| {
-        let nums: Vec<&str> = line.split_whitespace().collect();
+    for (i, line) in input.lines().enumerate() {
+        let mut nums = words(line);
+        let mut next_num = |expected| match nums.next() {
+            Some((column, token)) => decimal(token).ok_or_else(|| ParseError::InvalidNumber {
+                day: 1,
+                line: i + 1,
+                column,
+                token: token.to_string(),
+            }),
+            None => Err(ParseError::UnexpectedEnd {
+                day: 1,
+                line: i + 1,
+                column: line.len() + 1,
+                expected,
+            }),
+        };
 
-        let left_num: i64 = nums[0]
-            .parse()
-            .unwrap_or_else(|_| panic!("can't parse left number {}", nums[0]));
-        let right_num: i64 = nums[1]
-            .parse()
-            .unwrap_or_else(|_| panic!("can't parse right number {}", nums[1]));
+        let left_num = next_num("left number")?;
+        let right_num = next_num("right number")?;
+
+        if let Some((column, token)) = nums.next() {
+            return Err(ParseError::UnexpectedToken {
+                day: 1,
+                line: i + 1,
+                column,
+                token: token.to_string(),
+                expected: "end of line",
+            });
+        }
 
         left.push(left_num);
         right.push(right_num);
-    });
+    }
 
-    Day1Input { left, right }
+    Ok(Day1Input { left, right })
+}
+
+/// Decimal number in `token` read straight from its bytes, `None` if it
+/// isn't one or doesn't fit.
+fn decimal(token: &str) -> Option<i64> {
+    let (negative, digits) = match token.as_bytes() {
+        [b'-', digits @ ..] => (true, digits),
+        [b'+', digits @ ..] => (false, digits),
+        digits => (false, digits),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+
+    digits.iter().try_fold(0i64, |n, &b| {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        let (n, digit) = (n.checked_mul(10)?, (b - b'0') as i64);
+        if negative {
+            n.checked_sub(digit)
+        } else {
+            n.checked_add(digit)
+        }
+    })
+}
+
+/// `values` sorted by their bytes, least significant first, skipping the
+/// bytes they all share.
+fn radix_sorted(values: &[i64]) -> Vec<i64> {
+    // flipping the sign bit orders negative numbers first
+    let mut keys: Vec<u64> = values.iter().map(|&v| v as u64 ^ 1 << 63).collect();
+    let mut sorted = vec![0; keys.len()];
+
+    for shift in (0..64).step_by(8) {
+        let digit = |key: u64| (key >> shift) as usize & 0xff;
+
+        let mut counts = [0usize; 256];
+        for &key in &keys {
+            counts[digit(key)] += 1;
+        }
+        if counts.contains(&keys.len()) {
+            continue;
+        }
+
+        let mut offset = 0;
+        for count in counts.iter_mut() {
+            (*count, offset) = (offset, offset + *count);
+        }
+        for &key in &keys {
+            sorted[counts[digit(key)]] = key;
+            counts[digit(key)] += 1;
+        }
+        std::mem::swap(&mut keys, &mut sorted);
+    }
+
+    keys.into_iter().map(|key| (key ^ 1 << 63) as i64).collect()
+}
+
+/// Sum of `terms` as the answer of `part`, [`SolveError::Negative`] if it
+/// is below zero or [`SolveError::Overflow`] if it doesn't fit. Terms are
+/// wide enough that only the sum can overflow.
+fn checked_sum(part: u8, terms: impl IntoIterator<Item = i128>) -> Result<u64, SolveError> {
+    let sum = terms
+        .into_iter()
+        .try_fold(0i128, |sum, term| sum.checked_add(term))
+        .ok_or(SolveError::Overflow { day: 1, part })?;
+    if sum < 0 {
+        return Err(SolveError::Negative {
+            day: 1,
+            part,
+            answer: sum,
+        });
+    }
+
+    u64::try_from(sum).map_err(|_| SolveError::Overflow { day: 1, part })
 }
 
 #[aoc(day1, part1)]
-pub fn part1(input: &Day1Input) -> u32 {
-    let copy = input.clone();
-    let (mut left, mut right) = (copy.left.clone(), copy.right.clone());
+pub fn part1(input: &Day1Input) -> Result<u64, SolveError> {
+    let (mut left, mut right) = (input.left.clone(), input.right.clone());
 
     left.sort_unstable();
     right.sort_unstable();
 
-    let mut sum = 0;
-
-    left.iter().enumerate().for_each(|(i, _val)| {
-        sum += left[i].abs_diff(right[i]);
-    });
-
-    sum as u32
+    checked_sum(
+        1,
+        left.iter().zip(&right).map(|(a, b)| a.abs_diff(*b) as i128),
+    )
 }
 
 #[aoc(day1, part1, heap)]
-pub fn part1_heap(input: &Day1Input) -> u32 {
+pub fn part1_heap(input: &Day1Input) -> Result<u64, SolveError> {
     let mut left = BinaryHeap::new();
     let mut right = BinaryHeap::new();
 
     input.left.iter().for_each(|n| left.push(n));
     input.right.iter().for_each(|n| right.push(n));
 
-    let mut sum: u32 = 0;
-    while let Some(a) = left.pop() {
+    let diffs = std::iter::from_fn(|| {
+        let a = left.pop()?;
         let b = right.pop().expect("have right number");
-        sum += a.abs_diff(*b) as u32
-    }
-    sum
+        Some(a.abs_diff(*b) as i128)
+    });
+    checked_sum(1, diffs)
+}
+
+#[aoc(day1, part1, radix)]
+pub fn part1_radix(input: &Day1Input) -> Result<u64, SolveError> {
+    let (left, right) = (radix_sorted(&input.left), radix_sorted(&input.right));
+
+    checked_sum(
+        1,
+        left.iter().zip(&right).map(|(a, b)| a.abs_diff(*b) as i128),
+    )
 }
 
 #[aoc(day1, part2)]
-pub fn part2(input: &Day1Input) -> u32 {
-    let (left, right) = (input.left.clone(), input.right.clone());
+pub fn part2(input: &Day1Input) -> Result<u64, SolveError> {
     let mut freq = HashMap::<i64, i64>::new();
 
-    right.iter().for_each(|key| {
+    input.right.iter().for_each(|key| {
         let _ = *freq.entry(*key).and_modify(|e| *e += 1).or_insert(1);
     });
 
-    let mut similarity = 0;
+    checked_sum(
+        2,
+        input
+            .left
+            .iter()
+            .filter_map(|val| Some(*val as i128 * *freq.get(val)? as i128)),
+    )
+}
+
+/// Part 2 walking both lists sorted, multiplying the runs of every value
+/// found in both.
+#[aoc(day1, part2, merge)]
+pub fn part2_merge(input: &Day1Input) -> Result<u64, SolveError> {
+    let (left, right) = (radix_sorted(&input.left), radix_sorted(&input.right));
+    let run = |list: &[i64], value: i64| list.iter().take_while(|&&n| n == value).count();
 
-    left.iter().enumerate().for_each(|(i, val)| {
-        if let Some(count) = freq.get(val) {
-            similarity += left[i] * count;
+    let (mut i, mut j) = (0, 0);
+    let mut terms = vec![];
+    while i < left.len() && j < right.len() {
+        match left[i].cmp(&right[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                let value = left[i];
+                let (a, b) = (run(&left[i..], value), run(&right[j..], value));
+                terms.push(value as i128 * (a * b) as i128);
+                (i, j) = (i + a, j + b);
+            }
         }
-    });
+    }
+
+    checked_sum(2, terms)
+}
+
+/// Part 2 counting the right list in a flat array indexed by value, or
+/// [`part2_merge`] when its values span more than [`MAX_FREQUENCY_RANGE`].
+#[aoc(day1, part2, frequency)]
+pub fn part2_frequency(input: &Day1Input) -> Result<u64, SolveError> {
+    let (Some(&min), Some(&max)) = (input.right.iter().min(), input.right.iter().max()) else {
+        return Ok(0);
+    };
+    if max.abs_diff(min) >= MAX_FREQUENCY_RANGE as u64 {
+        return part2_merge(input);
+    }
+
+    let mut counts = vec![0u32; (max - min + 1) as usize];
+    for &n in &input.right {
+        counts[(n - min) as usize] += 1;
+    }
+
+    checked_sum(
+        2,
+        input
+            .left
+            .iter()
+            .filter(|&&n| (min..=max).contains(&n))
+            .map(|&n| n as i128 * counts[(n - min) as usize] as i128),
+    )
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Input = Day1Input;
+
+    fn parse(input: &str) -> Result<Day1Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Day1Input) -> Result<Answer, SolveError> {
+        part1(input)
+    }
+
+    fn part2(input: &Day1Input) -> Result<Answer, SolveError> {
+        part2(input)
+    }
 
-    similarity as u32
+    fn variants() -> Vec<Variant<Day1Input>> {
+        vec![
+            Variant {
+                part: Part::One,
+                name: "heap",
+                run: part1_heap,
+            },
+            Variant {
+                part: Part::One,
+                name: "radix",
+                run: part1_radix,
+            },
+            Variant {
+                part: Part::Two,
+                name: "merge",
+                run: part2_merge,
+            },
+            Variant {
+                part: Part::Two,
+                name: "frequency",
+                run: part2_frequency,
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::day1::*;
+    use crate::solution::{check_consistency, Part, DAYS};
+
+    #[test]
+    fn test_parse() {
+        let input = parse_input("3   4\n-4\t3\r\n  +2 5  \n").unwrap();
+        assert_eq!(vec![3, -4, 2], input.left);
+        assert_eq!(vec![4, 3, 5], input.right);
+
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 1,
+                line: 2,
+                column: 5,
+                token: "4x".to_string(),
+            },
+            parse_input("1 2\n3   4x").unwrap_err()
+        );
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 1,
+                line: 1,
+                column: 1,
+                token: "99999999999999999999".to_string(),
+            },
+            parse_input("99999999999999999999 1").unwrap_err()
+        );
+        assert!(matches!(
+            parse_input("1 2\n3"),
+            Err(ParseError::UnexpectedEnd { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_wide_answers() {
+        let check = |input: &str, part: Part| {
+            // errors too have to be the same in every variant
+            let answers = check_consistency(DAYS[0], input, part).unwrap().answers;
+            assert!(answers.iter().all(|(_, answer)| *answer == answers[0].1));
+            answers[0].1.clone()
+        };
+
+        // both totals are past u32::MAX
+        let input = "0   3000000000\n3000000000   3000000000\n1   3000000000";
+        assert_eq!(Ok(5_999_999_999), check(input, Part::One));
+        assert_eq!(Ok(9_000_000_000), check(input, Part::Two));
+
+        let overflow = |part| Err(SolveError::Overflow { day: 1, part });
+        let input = "-9223372036854775808   9223372036854775807\n\
+                     -9223372036854775808   9223372036854775807";
+        assert_eq!(overflow(1), check(input, Part::One));
+        // answers are unsigned
+        let negative = SolveError::Negative {
+            day: 1,
+            part: 2,
+            answer: -10,
+        };
+        assert_eq!("day 1 part 2: answer -10 is negative", negative.to_string());
+        assert_eq!(Err(negative), check("-5   -5\n1   -5", Part::Two));
+        let input = "9223372036854775807   9223372036854775807\n\
+                     9223372036854775807   9223372036854775807";
+        assert_eq!(overflow(2), check(input, Part::Two));
+    }
+
+    #[test]
+    fn test_radix_sort() {
+        let values = [300, -1, 0, i64::MIN, 5, 70_000, -300, i64::MAX, 5];
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+
+        assert_eq!(sorted, radix_sorted(&values));
+        assert_eq!(vec![7; 3], radix_sorted(&[7; 3]));
+        assert!(radix_sorted(&[]).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn test_variants_agree(pairs in prop::collection::vec((0i64..100_000, 0i64..100_000), 0..500)) {
+            let input: String = pairs
+                .iter()
+                .map(|(left, right)| format!("{}   {}\n", left, right))
+                .collect();
+
+            for part in Part::ALL {
+                let consistency = check_consistency(DAYS[0], &input, part).unwrap();
+                prop_assert!(consistency.is_consistent(), "{:?}", consistency);
+            }
+        }
+    }
 }
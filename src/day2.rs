@@ -1,79 +1,118 @@
-use aoc_runner_derive::aoc;
+use aoc_runner_derive::{aoc, aoc_generator};
 
-#[aoc(day2, part1)]
-pub fn part1(input: &str) -> u32 {
-    let reports = input
-        .lines()
-        .map(|line| {
-            line.split(" ")
-                .map(|n| {
-                    n.parse::<i32>()
-                        .unwrap_or_else(|x| panic!("error parsing {} as number", x))
-                })
-                .collect::<Vec<i32>>()
-        })
-        .collect::<Vec<Vec<i32>>>();
-
-    reports.iter().filter(|report| valid_report(report)).count() as u32
+use crate::error::{number, ParseError, SolveError};
+use crate::parse::words;
+use crate::solution::{Answer, Solution};
+
+pub mod policy;
+pub mod verdict;
+
+pub use policy::SafetyPolicy;
+pub use verdict::ReportVerdict;
+
+/// Levels of all the reports in one vector, each report a slice of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reports {
+    levels: Vec<i32>,
+    /// Start of every report in `levels`, and the end of the last one.
+    offsets: Vec<usize>,
 }
 
-fn valid_report(report: &[i32]) -> bool {
-    let ascending = report.iter().is_sorted();
-    let descending = report.iter().is_sorted_by(|a, b| a >= b);
+impl Default for Reports {
+    /// No reports, the end of the last one is the start of `levels`.
+    fn default() -> Reports {
+        Reports {
+            levels: vec![],
+            offsets: vec![0],
+        }
+    }
+}
 
-    let mut shifted_report = report.to_vec();
+impl Reports {
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
 
-    shifted_report.rotate_left(1);
-    // println!("report {:?}\nshifted {:?}", report, shifted_report);
-    let differ = report
-        .iter()
-        .zip(shifted_report.iter())
-        .take(report.len() - 1)
-        .all(|(a, b)| {
-            let diff = a.abs_diff(*b);
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<&[i32]> {
+        let (start, end) = (*self.offsets.get(i)?, *self.offsets.get(i + 1)?);
+        Some(&self.levels[start..end])
+    }
 
-            (1..=3).contains(&diff)
-        });
+    pub fn iter(&self) -> impl Iterator<Item = &[i32]> + '_ {
+        self.offsets
+            .windows(2)
+            .map(|range| &self.levels[range[0]..range[1]])
+    }
+}
+
+/// One report per line, levels separated by any whitespace. Blank lines are
+/// skipped.
+#[aoc_generator(day2)]
+pub fn parse_input(input: &str) -> Result<Reports, ParseError> {
+    let mut levels = vec![];
+    let mut offsets = vec![0];
 
-    // println!("asc {}, desc {}, diff {}", ascending, descending, differ);
+    for (i, line) in input.lines().enumerate() {
+        let start = levels.len();
+        for (column, token) in words(line) {
+            levels.push(number::<i32>(2, i + 1, column, token)?);
+        }
+        if levels.len() > start {
+            offsets.push(levels.len());
+        }
+    }
 
-    (ascending || descending) && differ
+    Ok(Reports { levels, offsets })
+}
+
+#[aoc(day2, part1)]
+pub fn part1(input: &Reports) -> u32 {
+    count_safe(input, &SafetyPolicy::STRICT)
 }
 
 #[aoc(day2, part2)]
-pub fn part2(input: &str) -> u32 {
-    let reports = input
-        .lines()
-        .map(|line| {
-            line.split(" ")
-                .map(|n| {
-                    n.parse::<i32>()
-                        .unwrap_or_else(|x| panic!("error parsing {} as number", x))
-                })
-                .collect::<Vec<i32>>()
-        })
-        .collect::<Vec<Vec<i32>>>();
+pub fn part2(input: &Reports) -> u32 {
+    count_safe(input, &SafetyPolicy::DAMPENED)
+}
 
+/// Number of reports `policy` accepts.
+pub fn count_safe(reports: &Reports, policy: &SafetyPolicy) -> u32 {
     reports
         .iter()
-        .filter(|report| valid_report(report) || damped_valid(report))
+        .filter(|report| policy.accepts(report))
         .count() as u32
 }
 
-fn damped_valid(report: &[i32]) -> bool {
-    (0..report.len()).any(|i| {
-        let damped_report = report
-            .iter()
-            .enumerate()
-            .filter_map(|(j, &num)| if j != i { Some(num) } else { None })
-            .collect::<Vec<i32>>();
-        valid_report(&damped_report)
-    })
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Input = Reports;
+
+    fn parse(input: &str) -> Result<Reports, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Reports) -> Result<Answer, SolveError> {
+        Ok(part1(input).into())
+    }
+
+    fn part2(input: &Reports) -> Result<Answer, SolveError> {
+        Ok(part2(input).into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day2::valid_report;
+    use proptest::prelude::*;
+
+    use crate::day2::{parse_input, part1, part2, Reports, SafetyPolicy};
+    use crate::error::ParseError;
 
     // 7 6 4 2 1: Safe because the levels are all decreasing by 1 or 2.
     // 1 2 7 8 9: Unsafe because 2 7 is an increase of 5.
@@ -83,7 +122,89 @@ mod tests {
     // 1 3 6 7 9: Safe because the levels are all increasing by 1, 2, or 3.
     #[test]
     fn test_report() {
-        assert!(valid_report(&[7, 6, 4, 2, 1]));
-        assert!(!valid_report(&[1, 2, 7, 8, 9]));
+        assert!(SafetyPolicy::STRICT.is_safe(&[7, 6, 4, 2, 1]));
+        assert!(!SafetyPolicy::STRICT.is_safe(&[1, 2, 7, 8, 9]));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            Err(ParseError::InvalidNumber {
+                day: 2,
+                line: 2,
+                column: 3,
+                token: "x".to_string(),
+            }),
+            parse_input("7 6 4 2 1\n1 x 7 8 9")
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let reports = parse_input("7  6\t4 2 1\n\n 1 3 2 4 5 \r\n8 6 4 4 1\n").unwrap();
+
+        assert_eq!(3, reports.len());
+        assert_eq!(Some(&[1, 3, 2, 4, 5][..]), reports.get(1));
+        assert_eq!(None, reports.get(3));
+        assert_eq!(
+            vec![5, 5, 5],
+            reports
+                .iter()
+                .map(|report| report.len())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, part1(&reports));
+        assert_eq!(3, part2(&reports));
+
+        let empty = Reports::default();
+        assert_eq!(0, empty.len());
+        assert!(empty.is_empty());
+        assert_eq!(None, empty.get(0));
+        assert_eq!(empty, parse_input("\n").unwrap());
+    }
+
+    #[test]
+    fn test_dampener() {
+        let dampener = SafetyPolicy::DAMPENED;
+        let tolerating = |tolerance| SafetyPolicy {
+            tolerance,
+            ..SafetyPolicy::STRICT
+        };
+
+        assert_eq!(Some(vec![]), dampener.dampen(&[7, 6, 4, 2, 1]));
+        assert_eq!(None, dampener.dampen(&[1, 2, 7, 8, 9]));
+        assert_eq!(Some(vec![2]), dampener.dampen(&[1, 3, 2, 4, 5]));
+        assert_eq!(Some(vec![3]), dampener.dampen(&[8, 6, 4, 4, 1]));
+
+        assert_eq!(
+            vec![0, 1],
+            SafetyPolicy::STRICT.min_removals(&[1, 2, 7, 8, 9])
+        );
+        assert_eq!(Some(vec![0, 1]), tolerating(2).dampen(&[1, 2, 7, 8, 9]));
+        assert_eq!(
+            Some(vec![0, 2, 5]),
+            tolerating(3).dampen(&[9, 1, 0, 2, 3, 0, 6])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_min_removals(report in prop::collection::vec(0i32..12, 1..9)) {
+            let removed = SafetyPolicy::STRICT.min_removals(&report);
+            let kept: Vec<i32> = (0..report.len())
+                .filter(|i| !removed.contains(i))
+                .map(|i| report[i])
+                .collect();
+            prop_assert!(SafetyPolicy::STRICT.is_safe(&kept));
+
+            // no subset with fewer removals is safe
+            let n = report.len();
+            for mask in 0u32..1 << n {
+                let kept: Vec<i32> = (0..n).filter(|i| mask & 1 << i != 0).map(|i| report[i]).collect();
+                if !kept.is_empty() && SafetyPolicy::STRICT.is_safe(&kept) {
+                    prop_assert!(n - kept.len() >= removed.len());
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,398 @@
+//! Rules a report has to follow to be safe, so they can be changed without
+//! touching the solutions.
+
+use std::collections::HashMap;
+
+use crate::day2::verdict::{Direction, Violation};
+
+/// Last level of a subsequence, the way it last went (`None` while flat) and
+/// how many times it changed direction.
+type Key = (i32, Option<Direction>, usize);
+
+/// Reports longer than this look the keys that a level can follow up by
+/// value, instead of trying every key seen so far.
+const INDEX_MIN_LEN: usize = 32;
+
+/// Up to this many removals only the few subsequences ending on the last
+/// levels can still be kept, and trying them all beats hashing values
+/// however long the report.
+const SCAN_MAX_REMOVALS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafetyPolicy {
+    /// Smallest step between two levels that aren't equal.
+    pub min_step: u32,
+    /// Largest step between two levels.
+    pub max_step: u32,
+    /// Whether two equal levels in a row are allowed.
+    pub allow_plateaus: bool,
+    /// How many times a report may go from increasing to decreasing or back.
+    pub direction_changes: usize,
+    /// How many bad levels the Problem Dampener may remove.
+    pub tolerance: usize,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> SafetyPolicy {
+        SafetyPolicy::STRICT
+    }
+}
+
+impl SafetyPolicy {
+    /// Part 1: levels all increasing or all decreasing by 1 to 3.
+    pub const STRICT: SafetyPolicy = SafetyPolicy {
+        min_step: 1,
+        max_step: 3,
+        allow_plateaus: false,
+        direction_changes: 0,
+        tolerance: 0,
+    };
+
+    /// Part 2: the same with the Problem Dampener removing one bad level.
+    pub const DAMPENED: SafetyPolicy = SafetyPolicy {
+        tolerance: 1,
+        ..SafetyPolicy::STRICT
+    };
+
+    /// Key of a subsequence ending on `key` after adding `level`, or the
+    /// rule the step to `level` breaks.
+    pub(crate) fn follow(
+        &self,
+        (last, direction, changes): Key,
+        level: i32,
+    ) -> Result<Key, Violation> {
+        let step = level as i64 - last as i64;
+        if step == 0 && !self.allow_plateaus {
+            return Err(Violation::Zero);
+        }
+        if step == 0 {
+            return Ok((level, direction, changes));
+        }
+        if step.unsigned_abs() < self.min_step as u64 {
+            return Err(Violation::TooSmall { min: self.min_step });
+        }
+        if step.unsigned_abs() > self.max_step as u64 {
+            return Err(Violation::TooLarge { max: self.max_step });
+        }
+
+        let way = if step > 0 {
+            Direction::Ascending
+        } else {
+            Direction::Descending
+        };
+        let changes = changes + direction.is_some_and(|d| d != way) as usize;
+        if changes > self.direction_changes {
+            return Err(Violation::DirectionChange {
+                allowed: self.direction_changes,
+            });
+        }
+        Ok((level, Some(way), changes))
+    }
+
+    /// Whether `report` follows the rules as it is, without the dampener.
+    pub fn is_safe(&self, report: &[i32]) -> bool {
+        let Some((&first, rest)) = report.split_first() else {
+            return true;
+        };
+
+        rest.iter()
+            .try_fold((first, None, 0), |key, &level| self.follow(key, level))
+            .is_ok()
+    }
+
+    /// Whether `report` is safe once the dampener removed up to `tolerance`
+    /// levels.
+    pub fn accepts(&self, report: &[i32]) -> bool {
+        self.dampen(report).is_some()
+    }
+
+    /// Indices of the levels to remove to make `report` safe, or `None` if
+    /// that takes more than `tolerance` removals.
+    pub fn dampen(&self, report: &[i32]) -> Option<Vec<usize>> {
+        self.removals(report, self.tolerance)
+    }
+
+    /// Indices of the fewest levels to remove from `report` to make it safe,
+    /// in increasing order, ignoring `tolerance`.
+    ///
+    /// Finds the longest subsequence following the rules, remembering only
+    /// the longest one ending on every [`Key`]. Levels are integers, so when
+    /// the allowed steps are few a level can only follow a few values and
+    /// this takes linear time on long reports, otherwise every key seen so
+    /// far is tried. With a small `tolerance` [`SafetyPolicy::dampen`] only
+    /// keeps the keys that can still be extended within it, and takes
+    /// linear time whatever the steps.
+    pub fn min_removals(&self, report: &[i32]) -> Vec<usize> {
+        self.removals(report, usize::MAX)
+            .expect("removing every level is safe")
+    }
+
+    /// [`SafetyPolicy::min_removals`], giving up with `None` as soon as it
+    /// takes more than `limit` removals.
+    fn removals(&self, report: &[i32], limit: usize) -> Option<Vec<usize>> {
+        // most reports are safe already, skip the hashing for them
+        if self.is_safe(report) {
+            return Some(vec![]);
+        }
+        if limit == 0 {
+            return None;
+        }
+
+        // level index and previous node of every subsequence kept
+        let mut nodes: Vec<(usize, Option<usize>)> = vec![];
+        // every key with the length and last node of the longest subsequence
+        // ending on it
+        let mut ends: Vec<(Key, usize, usize)> = vec![];
+        // positions in `ends` of the keys ending on each value, only worth
+        // hashing for long reports
+        let mut by_value: HashMap<i32, Vec<usize>> = HashMap::new();
+        let mut longest: Option<(usize, usize)> = None;
+
+        let min_step = self.min_step.max(1) as i64;
+        let steps = (self.max_step as i64 - min_step + 1).max(0) as usize;
+        let indexed =
+            limit > SCAN_MAX_REMOVALS && report.len() > INDEX_MIN_LEN && 2 * steps < report.len();
+        // subsequences ending on the current level, reused for every level
+        let mut found: Vec<(Key, usize, Option<usize>)> = vec![];
+
+        for (i, &level) in report.iter().enumerate() {
+            if !indexed {
+                // ending before the last `limit` levels missed, a subsequence
+                // can't be extended without removing too many
+                ends.retain(|&(_, len, _)| i - len <= limit);
+            }
+
+            found.clear();
+            found.push(((level, None, 0), 1, None));
+            let mut extend = |&(key, len, node): &(Key, usize, usize)| {
+                if let Ok(next) = self.follow(key, level) {
+                    found.push((next, len + 1, Some(node)));
+                }
+            };
+
+            if indexed {
+                let values = (min_step..min_step + steps as i64)
+                    .flat_map(|step| [level as i64 - step, level as i64 + step])
+                    .chain(self.allow_plateaus.then_some(level as i64))
+                    .filter_map(|value| i32::try_from(value).ok());
+                for value in values {
+                    for &end in by_value.get(&value).into_iter().flatten() {
+                        extend(&ends[end]);
+                    }
+                }
+            } else {
+                ends.iter().for_each(extend);
+            }
+
+            for (key, len, prev) in found.drain(..) {
+                let node = nodes.len();
+                let existing = if indexed {
+                    by_value
+                        .get(&key.0)
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .find(|&end| ends[end].0 == key)
+                } else {
+                    ends.iter().position(|end| end.0 == key)
+                };
+                match existing {
+                    Some(end) if ends[end].1 >= len => continue,
+                    Some(end) => ends[end] = (key, len, node),
+                    None => {
+                        if indexed {
+                            by_value.entry(key.0).or_default().push(ends.len());
+                        }
+                        ends.push((key, len, node));
+                    }
+                }
+
+                nodes.push((i, prev));
+                if longest.is_none_or(|(best, _)| len > best) {
+                    longest = Some((len, node));
+                }
+            }
+
+            // even keeping every level left, the levels up to here not in
+            // the longest subsequence are removed
+            let kept = longest.map_or(0, |(len, _)| len);
+            if i + 1 - kept > limit {
+                return None;
+            }
+        }
+
+        let (len, mut node) = longest.map_or((0, None), |(len, node)| (len, Some(node)));
+        if report.len() - len > limit {
+            return None;
+        }
+
+        // the levels between the kept ones, walking the subsequence backwards
+        let mut removed = Vec::with_capacity(report.len() - len);
+        let mut end = report.len();
+        while let Some(n) = node {
+            let (kept, prev) = nodes[n];
+            removed.extend((kept + 1..end).rev());
+            end = kept;
+            node = prev;
+        }
+        removed.extend((0..end).rev());
+        removed.reverse();
+        Some(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::day2::policy::*;
+
+    #[test]
+    fn test_policy() {
+        let strict = SafetyPolicy::STRICT;
+        assert!(strict.is_safe(&[7, 6, 4, 2, 1]));
+        assert!(!strict.is_safe(&[8, 6, 4, 4, 1]));
+        assert!(!strict.accepts(&[1, 3, 2, 4, 5]));
+        assert!(SafetyPolicy::DAMPENED.accepts(&[1, 3, 2, 4, 5]));
+
+        let plateaus = SafetyPolicy {
+            allow_plateaus: true,
+            ..strict
+        };
+        assert!(plateaus.is_safe(&[8, 6, 4, 4, 1]));
+        assert!(!plateaus.is_safe(&[1, 3, 2, 4, 5]));
+
+        let one_change = SafetyPolicy {
+            direction_changes: 1,
+            ..strict
+        };
+        assert!(one_change.is_safe(&[1, 3, 2, 1]));
+        assert!(!one_change.is_safe(&[1, 3, 2, 4]));
+
+        let big_steps = SafetyPolicy {
+            min_step: 2,
+            max_step: 5,
+            ..strict
+        };
+        assert!(big_steps.is_safe(&[1, 6, 8, 13]));
+        assert!(!big_steps.is_safe(&[1, 2, 7]));
+    }
+
+    #[test]
+    fn test_min_removals() {
+        assert_eq!(
+            vec![0, 1],
+            SafetyPolicy::STRICT.min_removals(&[1, 2, 7, 8, 9])
+        );
+
+        // up and down again instead of removing the bump
+        let one_change = SafetyPolicy {
+            direction_changes: 1,
+            ..SafetyPolicy::STRICT
+        };
+        assert_eq!(vec![] as Vec<usize>, one_change.min_removals(&[1, 3, 2, 1]));
+        assert_eq!(vec![4], one_change.min_removals(&[1, 3, 2, 1, 4]));
+
+        let any_step = SafetyPolicy {
+            max_step: u32::MAX,
+            ..SafetyPolicy::STRICT
+        };
+        assert_eq!(vec![2], any_step.min_removals(&[-100, 1000, 0, 2000]));
+
+        // long enough to look levels up by value
+        let mut report: Vec<i32> = (0..200).collect();
+        for i in [5, 50, 51, 120] {
+            report[i] = -1;
+        }
+        assert_eq!(
+            vec![5, 50, 51, 120],
+            SafetyPolicy::STRICT.min_removals(&report)
+        );
+    }
+
+    #[test]
+    fn test_generated_long_reports() {
+        let input = crate::gen::generate(2, 3, 100_000).unwrap();
+        let reports = crate::day2::parse_input(&input).unwrap();
+        assert!(reports.iter().any(|report| report.len() > INDEX_MIN_LEN));
+
+        let policy = SafetyPolicy::DAMPENED;
+        for report in reports.iter().filter(|report| report.len() > INDEX_MIN_LEN) {
+            let removing_one = (0..report.len()).any(|skip| {
+                let mut kept = report.to_vec();
+                kept.remove(skip);
+                policy.is_safe(&kept)
+            });
+            assert_eq!(
+                policy.is_safe(report) || removing_one,
+                policy.accepts(report),
+                "{:?}",
+                report
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_fewest_removals(
+            report in prop::collection::vec(0i32..12, 1..9),
+            min_step in 0u32..3,
+            max_step in 0u32..12,
+            allow_plateaus: bool,
+            direction_changes in 0usize..3,
+        ) {
+            let policy = SafetyPolicy {
+                min_step,
+                max_step,
+                allow_plateaus,
+                direction_changes,
+                tolerance: 0,
+            };
+            let removed = policy.min_removals(&report);
+            let keep = |mask: u32| -> Vec<i32> {
+                (0..report.len()).filter(|i| mask & 1 << i != 0).map(|i| report[i]).collect()
+            };
+            let mask = (0..report.len()).filter(|i| !removed.contains(i)).fold(0, |mask, i| mask | 1 << i);
+            prop_assert!(policy.is_safe(&keep(mask)));
+
+            // no subset with fewer removals is safe
+            for mask in 0u32..1 << report.len() {
+                if policy.is_safe(&keep(mask)) {
+                    prop_assert!(mask.count_ones() as usize <= report.len() - removed.len());
+                }
+            }
+        }
+
+        #[test]
+        fn test_tolerance(
+            report in prop::collection::vec(0i32..12, 1..12),
+            max_step in 1u32..5,
+            allow_plateaus: bool,
+            direction_changes in 0usize..2,
+            tolerance in 0usize..3,
+        ) {
+            let policy = SafetyPolicy {
+                max_step,
+                allow_plateaus,
+                direction_changes,
+                tolerance,
+                ..SafetyPolicy::STRICT
+            };
+
+            // every way of keeping all the levels but up to `tolerance`
+            let safe = (0u32..1 << report.len())
+                .filter(|mask| report.len() - mask.count_ones() as usize <= tolerance)
+                .any(|mask| {
+                    let kept: Vec<i32> = (0..report.len())
+                        .filter(|i| mask & 1 << i != 0)
+                        .map(|i| report[i])
+                        .collect();
+                    policy.is_safe(&kept)
+                });
+            prop_assert_eq!(safe, policy.accepts(&report));
+            if let Some(removed) = policy.dampen(&report) {
+                prop_assert!(removed.len() <= tolerance);
+            }
+        }
+    }
+}
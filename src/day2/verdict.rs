@@ -0,0 +1,303 @@
+//! Why a report is safe or not, in the words of the puzzle.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::day2::SafetyPolicy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// Step of less than the policy's `min`, but not zero.
+    TooSmall { min: u32 },
+    /// Step of more than the policy's `max`.
+    TooLarge { max: u32 },
+    /// Two equal levels in a row.
+    Zero,
+    /// Step going the other way than the levels before it, once the report
+    /// already changed direction as many times as `allowed`.
+    DirectionChange { allowed: usize },
+}
+
+/// First pair of adjacent levels making a report unsafe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Problem {
+    /// Index of the first level of the pair.
+    pub index: usize,
+    pub pair: (i32, i32),
+    pub violation: Violation,
+}
+
+/// Whether a report is safe and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportVerdict {
+    pub safe: bool,
+    /// Way the report goes, given by its first step that isn't zero.
+    pub direction: Option<Direction>,
+    /// First problem of the report as it is, before any dampening.
+    pub problem: Option<Problem>,
+    /// Indices of the levels the dampener removed to make the report safe.
+    pub removed: Vec<usize>,
+}
+
+impl ReportVerdict {
+    /// Verdict of `report` under `policy`, without the dampener.
+    pub fn new(report: &[i32], policy: &SafetyPolicy) -> ReportVerdict {
+        let pairs = report.windows(2).map(|pair| (pair[0], pair[1]));
+        let direction = pairs.clone().find_map(|(a, b)| match a.cmp(&b) {
+            Ordering::Equal => None,
+            Ordering::Less => Some(Direction::Ascending),
+            Ordering::Greater => Some(Direction::Descending),
+        });
+
+        let mut key = (report.first().copied().unwrap_or_default(), None, 0);
+        let problem =
+            pairs
+                .enumerate()
+                .find_map(|(index, pair)| match policy.follow(key, pair.1) {
+                    Ok(next) => {
+                        key = next;
+                        None
+                    }
+                    Err(violation) => Some(Problem {
+                        index,
+                        pair,
+                        violation,
+                    }),
+                });
+
+        ReportVerdict {
+            safe: problem.is_none(),
+            direction,
+            problem,
+            removed: vec![],
+        }
+    }
+
+    /// Verdict of `report` under `policy`, with the dampener removing up to
+    /// its `tolerance` bad levels.
+    pub fn dampened(report: &[i32], policy: &SafetyPolicy) -> ReportVerdict {
+        let verdict = ReportVerdict::new(report, policy);
+        if verdict.safe || policy.tolerance == 0 {
+            return verdict;
+        }
+
+        match policy.dampen(report) {
+            Some(removed) => {
+                let kept: Vec<i32> = (0..report.len())
+                    .filter(|i| !removed.contains(i))
+                    .map(|i| report[i])
+                    .collect();
+
+                ReportVerdict {
+                    safe: true,
+                    direction: ReportVerdict::new(&kept, policy).direction,
+                    removed,
+                    ..verdict
+                }
+            }
+            None => verdict,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Ascending => write!(f, "increasing"),
+            Direction::Descending => write!(f, "decreasing"),
+        }
+    }
+}
+
+impl fmt::Display for ReportVerdict {
+    /// E.g. `unsafe: 2 7 is an increase of 5` or `safe after removing
+    /// level 3: 3 2 is decreasing but the levels before are increasing`,
+    /// with levels counted from 1.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.safe, self.removed.as_slice()) {
+            (false, _) => write!(f, "unsafe")?,
+            (true, []) => write!(f, "safe")?,
+            (true, [level]) => write!(f, "safe after removing level {}", level + 1)?,
+            (true, levels) => {
+                let levels: Vec<String> = levels.iter().map(|i| (i + 1).to_string()).collect();
+                write!(f, "safe after removing levels {}", levels.join(", "))?
+            }
+        }
+
+        let Some(problem) = self.problem else {
+            return match self.direction {
+                Some(direction) => write!(f, ", {}", direction),
+                None => Ok(()),
+            };
+        };
+
+        let (a, b) = problem.pair;
+        let (way, other) = if b > a {
+            ("increasing", "decreasing")
+        } else {
+            ("decreasing", "increasing")
+        };
+        let change = if b > a { "an increase" } else { "a decrease" };
+        match problem.violation {
+            Violation::Zero => write!(f, ": {} {} is neither an increase or a decrease", a, b),
+            Violation::TooSmall { min } => write!(
+                f,
+                ": {} {} is {} of {}, less than {}",
+                a,
+                b,
+                change,
+                a.abs_diff(b),
+                min
+            ),
+            Violation::TooLarge { max } => write!(
+                f,
+                ": {} {} is {} of {}, more than {}",
+                a,
+                b,
+                change,
+                a.abs_diff(b),
+                max
+            ),
+            Violation::DirectionChange { allowed: 0 } => write!(
+                f,
+                ": {} {} is {} but the levels before are {}",
+                a, b, way, other
+            ),
+            Violation::DirectionChange { allowed } => write!(
+                f,
+                ": {} {} is {} but the levels before are {}, after {} changes of direction",
+                a, b, way, other, allowed
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day2::verdict::*;
+
+    #[test]
+    fn test_verdict() {
+        let verdicts: Vec<String> = [
+            vec![7, 6, 4, 2, 1],
+            vec![1, 2, 7, 8, 9],
+            vec![9, 7, 6, 2, 1],
+            vec![1, 3, 2, 4, 5],
+            vec![8, 6, 4, 4, 1],
+            vec![1, 3, 6, 7, 9],
+        ]
+        .iter()
+        .map(|report| ReportVerdict::new(report, &SafetyPolicy::STRICT).to_string())
+        .collect();
+
+        assert_eq!(
+            vec![
+                "safe, decreasing",
+                "unsafe: 2 7 is an increase of 5, more than 3",
+                "unsafe: 6 2 is a decrease of 4, more than 3",
+                "unsafe: 3 2 is decreasing but the levels before are increasing",
+                "unsafe: 4 4 is neither an increase or a decrease",
+                "safe, increasing",
+            ],
+            verdicts
+        );
+
+        assert_eq!(
+            Some(Problem {
+                index: 1,
+                pair: (3, 2),
+                violation: Violation::DirectionChange { allowed: 0 }
+            }),
+            ReportVerdict::new(&[1, 3, 2, 4, 5], &SafetyPolicy::STRICT).problem
+        );
+    }
+
+    #[test]
+    fn test_extreme_levels() {
+        let verdict =
+            |report: &[i32]| ReportVerdict::new(report, &SafetyPolicy::STRICT).to_string();
+
+        assert_eq!(
+            "unsafe: -2147483648 2147483647 is an increase of 4294967295, more than 3",
+            verdict(&[i32::MIN, i32::MAX])
+        );
+        assert_eq!(
+            "unsafe: 2147483647 -2147483648 is a decrease of 4294967295, more than 3",
+            verdict(&[i32::MAX, i32::MIN])
+        );
+    }
+
+    #[test]
+    fn test_policies() {
+        let policy = SafetyPolicy {
+            min_step: 2,
+            max_step: 5,
+            allow_plateaus: true,
+            direction_changes: 1,
+            tolerance: 0,
+        };
+        let verdict = |report: &[i32]| ReportVerdict::new(report, &policy);
+
+        for report in [
+            [1, 6, 6, 4, 2],
+            [1, 2, 4, 6, 8],
+            [1, 3, 1, 3, 5],
+            [1, 7, 9, 11, 13],
+        ] {
+            assert_eq!(policy.is_safe(&report), verdict(&report).safe);
+        }
+        assert_eq!("safe, increasing", verdict(&[1, 6, 6, 4, 2]).to_string());
+        assert_eq!(
+            "unsafe: 1 2 is an increase of 1, less than 2",
+            verdict(&[1, 2, 4, 6, 8]).to_string()
+        );
+        assert_eq!(
+            "unsafe: 1 3 is increasing but the levels before are decreasing, after 1 changes of direction",
+            verdict(&[1, 3, 1, 3, 5]).to_string()
+        );
+        assert_eq!(
+            Some(Violation::TooLarge { max: 5 }),
+            verdict(&[1, 7, 9, 11, 13])
+                .problem
+                .map(|problem| problem.violation)
+        );
+    }
+
+    #[test]
+    fn test_dampened() {
+        let verdict = ReportVerdict::dampened(&[1, 3, 2, 4, 5], &SafetyPolicy::DAMPENED);
+
+        assert!(verdict.safe);
+        assert_eq!(vec![2], verdict.removed);
+        assert_eq!(Some(Direction::Ascending), verdict.direction);
+        assert_eq!(
+            "safe after removing level 3: 3 2 is decreasing but the levels before are increasing",
+            verdict.to_string()
+        );
+
+        let verdict = ReportVerdict::dampened(&[1, 2, 7, 8, 9], &SafetyPolicy::DAMPENED);
+        assert!(!verdict.safe);
+        let two = SafetyPolicy {
+            tolerance: 2,
+            ..SafetyPolicy::STRICT
+        };
+        assert_eq!(
+            "safe after removing levels 1, 2: 2 7 is an increase of 5, more than 3",
+            ReportVerdict::dampened(&[1, 2, 7, 8, 9], &two).to_string()
+        );
+
+        // the direction is the one left after the removal
+        let verdict = ReportVerdict::dampened(&[5, 6, 4, 3, 2], &SafetyPolicy::DAMPENED);
+        assert_eq!(Some(Direction::Descending), verdict.direction);
+
+        // no dampener at all
+        let verdict = ReportVerdict::dampened(&[1, 3, 2, 4, 5], &SafetyPolicy::STRICT);
+        assert!(!verdict.safe);
+    }
+}
@@ -1,97 +1,140 @@
-use aoc_runner_derive::aoc;
-use nom::{branch::alt, bytes::complete::tag, character::complete::anychar, multi::many0, IResult};
-use regex::Regex;
+use std::io::{self, BufRead};
 
-#[aoc(day3, part1)]
-pub fn mul(input: &str) -> u64 {
-    let re = Regex::new(r"mul\((?<a>\d{1,3}),(?<b>\d{1,3})\)").unwrap();
+use aoc_runner_derive::{aoc, aoc_generator};
+
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Solution};
 
-    let mut sum: u64 = 0;
+pub mod annotate;
+pub mod interpreter;
+pub mod scanner;
 
-    for (_, [a, b]) in re.captures_iter(input).map(|x| x.extract()) {
-        let a_num = a
-            .parse::<u64>()
-            .unwrap_or_else(|astr| panic!("error parsing {} as number", astr));
+pub use annotate::{annotate, Annotation};
+pub use interpreter::{Instruction, Interpreter};
+pub use scanner::{scan, Instructions, Scanner};
 
-        let b_num = b
-            .parse::<u64>()
-            .unwrap_or_else(|astr| panic!("error parsing {} as number", astr));
+/// Sum of the multiplications among `instructions`, skipping the ones
+/// turned off by `don't()` when `conditionals`.
+pub fn total(instructions: impl IntoIterator<Item = Instruction>, conditionals: bool) -> u64 {
+    let mut enabled = true;
+    let mut sum = 0;
 
-        sum += a_num * b_num;
+    for instruction in instructions {
+        match instruction {
+            Instruction::Mul(a, b) if enabled || !conditionals => sum += a * b,
+            Instruction::Do => enabled = true,
+            Instruction::Dont => enabled = false,
+            _ => {}
+        }
     }
 
     sum
 }
 
-fn do_(input: &str) -> IResult<&str, &str> {
-    tag("do()")(input)
-}
+/// [`total`] of a program read from `reader`, in constant memory however
+/// big it is.
+pub fn total_reader(reader: impl BufRead, conditionals: bool) -> io::Result<u64> {
+    let mut error = None;
+    let instructions = Instructions::new(reader).map_while(|instruction| match instruction {
+        Ok(spanned) => Some(spanned.instruction),
+        Err(e) => {
+            error = Some(e);
+            None
+        }
+    });
 
-fn dont(input: &str) -> IResult<&str, &str> {
-    tag("don't()")(input)
+    let sum = total(instructions, conditionals);
+    error.map_or(Ok(sum), Err)
 }
 
-fn mul_numbers(input: &str) -> IResult<&str, (u64, u64)> {
-    let (input, _) = tag("mul(")(input)?;
-    let (input, num1) = nom::character::complete::u64(input)?;
-    let (input, _) = tag(",")(input)?;
-    let (input, num2) = nom::character::complete::u64(input)?;
-    let (input, _) = tag(")")(input)?;
-    Ok((input, (num1, num2)))
+/// The instructions of a program, in order. Anything else is corrupted
+/// memory, so there is nothing to fail on.
+#[aoc_generator(day3)]
+pub fn parse_input(input: &str) -> Vec<Instruction> {
+    scan(input).map(|spanned| spanned.instruction).collect()
 }
 
-// Parser for garbage - consumes one char
-fn garbage(input: &str) -> IResult<&str, char> {
-    anychar(input)
+#[aoc(day3, part1)]
+pub fn mul(instructions: &[Instruction]) -> u64 {
+    total(instructions.iter().cloned(), false)
 }
 
-#[derive(Debug)]
-enum PatternType {
-    Mul((u64, u64)),
-    Do(()),
-    Dont(()),
-    Garbage(()),
+#[aoc(day3, part2)]
+pub fn dot(instructions: &[Instruction]) -> u64 {
+    total(instructions.iter().cloned(), true)
 }
 
-// Combined parser for any valid pattern
-fn pattern(input: &str) -> IResult<&str, PatternType> {
-    alt((
-        |input| mul_numbers(input).map(|(next, (a, b))| (next, PatternType::Mul((a, b)))),
-        |input| do_(input).map(|(next, _res)| (next, PatternType::Do(()))),
-        |input| dont(input).map(|(next, _res)| (next, PatternType::Dont(()))),
-    ))(input)
-}
+pub struct Day3;
 
-#[aoc(day3, part2)]
-pub fn dot(input: &str) -> u64 {
-    let mut parser = many0(alt((
-        |input| pattern(input),
-        |input| garbage(input).map(|(next, _res)| (next, PatternType::Garbage(()))),
-    )));
+impl Solution for Day3 {
+    const DAY: u8 = 3;
 
-    let mut enabled = true;
-    let mut sum: u64 = 0;
+    type Input = Vec<Instruction>;
 
-    let (_extra, instructions) = parser(input).expect("parsed successfully");
+    fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+        Ok(parse_input(input))
+    }
 
-    // println!("{:?} \nextra: {:?}", instructions, extra);
+    fn part1(input: &Vec<Instruction>) -> Result<Answer, SolveError> {
+        Ok(mul(input))
+    }
 
-    for instruction in instructions {
-        match instruction {
-            PatternType::Mul((a, b)) => {
-                if enabled && a < 1000 && b < 1000 {
-                    sum += a * b;
-                }
-            }
-            PatternType::Do(_) => {
-                enabled = true;
-            }
-            PatternType::Dont(_) => {
-                enabled = false;
+    fn part2(input: &Vec<Instruction>) -> Result<Answer, SolveError> {
+        Ok(dot(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+
+    use crate::day3::*;
+    use crate::inputs::Inputs;
+
+    fn example(name: &str) -> String {
+        Inputs::fixtures().load(3, name).unwrap()
+    }
+
+    #[test]
+    fn test_total_reader() {
+        let programs = [
+            example("example"),
+            example("example2"),
+            "mul(123,456)do()don't()mul(1,1)".to_string(),
+        ];
+        for program in &programs {
+            for capacity in [1, 2, 3, 5, 4096] {
+                // with 3 bytes `mul(` is split between two reads
+                let reader = || BufReader::with_capacity(capacity, program.as_bytes());
+                let instructions = parse_input(program);
+                assert_eq!(mul(&instructions), total_reader(reader(), false).unwrap());
+                assert_eq!(dot(&instructions), total_reader(reader(), true).unwrap());
             }
-            PatternType::Garbage(_) => {}
         }
+        assert_eq!(161, mul(&parse_input(&programs[0])));
+        assert_eq!(48, dot(&parse_input(&programs[1])));
+
+        let program = crate::gen::generate(3, 5, 20_000).unwrap();
+        let reader = BufReader::with_capacity(7, program.as_bytes());
+        assert_eq!(
+            dot(&parse_input(&program)),
+            total_reader(reader, true).unwrap()
+        );
     }
 
-    sum
+    #[test]
+    fn test_total_reader_error() {
+        struct Broken;
+
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+        }
+
+        let program = example("example");
+        let reader = BufReader::new(program.as_bytes().chain(Broken));
+        let err = total_reader(reader, false).unwrap_err();
+        assert_eq!("broken", err.to_string());
+    }
 }
@@ -0,0 +1,306 @@
+//! Corrupted memory printed back with what the solutions made of it, to see
+//! which instructions were counted.
+
+use std::fmt::Write;
+
+use crossterm::style::{Color, Stylize};
+
+use crate::day3::interpreter::{Instruction, Span};
+use crate::day3::scanner::scan;
+
+/// Starts of instructions, `don't` before `do` so it is found whole.
+const PREFIXES: [&str; 3] = ["don't", "mul", "do"];
+
+/// Longest tail of a near miss after its prefix.
+const MAX_TAIL: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Multiplication added to the sum, which is `sum` after it.
+    Counted {
+        product: u64,
+        sum: u64,
+    },
+    /// Multiplication skipped after a `don't()`.
+    Disabled,
+    Do,
+    Dont,
+    /// Text that looks like an instruction but isn't one, e.g. `mul(1000,2)`
+    /// or `mul( 1,2)`.
+    NearMiss,
+}
+
+/// What a part of the program was taken for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub span: Span,
+    pub kind: Kind,
+}
+
+/// Annotations of `program` in order, with `do()` and `don't()` turning
+/// multiplications on and off when `conditionals`, like part 2. Without
+/// `conditionals` they mean nothing, and are left as plain text.
+pub fn annotate(program: &str, conditionals: bool) -> Vec<Annotation> {
+    let mut annotations = vec![];
+    let mut instructions = scan(program).peekable();
+    let (mut enabled, mut sum) = (true, 0);
+    let mut i = 0;
+
+    while i < program.len() {
+        if let Some(spanned) = instructions.next_if(|spanned| spanned.span.start == i) {
+            let kind = match spanned.instruction {
+                Instruction::Mul(a, b) if enabled => {
+                    sum += a * b;
+                    Some(Kind::Counted {
+                        product: a * b,
+                        sum,
+                    })
+                }
+                Instruction::Mul(_, _) => Some(Kind::Disabled),
+                Instruction::Do => {
+                    enabled = true;
+                    conditionals.then_some(Kind::Do)
+                }
+                Instruction::Dont => {
+                    enabled = !conditionals;
+                    conditionals.then_some(Kind::Dont)
+                }
+                Instruction::Custom { .. } => {
+                    unreachable!("the scanner has no custom instructions")
+                }
+            };
+
+            i = spanned.span.end;
+            if let Some(kind) = kind {
+                annotations.push(Annotation {
+                    span: spanned.span,
+                    kind,
+                });
+            }
+        } else if let Some(len) = near_miss(&program[i..]) {
+            annotations.push(Annotation {
+                span: i..i + len,
+                kind: Kind::NearMiss,
+            });
+            i += len;
+        } else {
+            i += program[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    annotations
+}
+
+/// Length of the near miss at the start of `input`: the start of an
+/// instruction followed by what could have been its arguments, up to the
+/// first closing bracket.
+fn near_miss(input: &str) -> Option<usize> {
+    let prefix = PREFIXES.iter().find(|prefix| input.starts_with(*prefix))?;
+
+    let mut len = 0;
+    for c in input[prefix.len()..].chars().take(MAX_TAIL) {
+        if !matches!(c, '0'..='9' | ',' | ' ' | '(' | ')' | '[' | ']') {
+            break;
+        }
+        len += 1;
+        if matches!(c, ')' | ']') {
+            break;
+        }
+    }
+
+    (len > 0).then_some(prefix.len() + len)
+}
+
+/// Sum after the last counted multiplication.
+pub fn total(annotations: &[Annotation]) -> u64 {
+    annotations
+        .iter()
+        .rev()
+        .find_map(|annotation| match annotation.kind {
+            Kind::Counted { sum, .. } => Some(sum),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+/// `program` with counted multiplications followed by the running sum as
+/// `[=sum]`, disabled ones by `[off]` and near misses by `[?]`. With `color`
+/// counted multiplications are green, disabled ones grey, `do()` and
+/// `don't()` blue and near misses red.
+pub fn to_text(program: &str, annotations: &[Annotation], color: bool) -> String {
+    let mut text = String::new();
+    let mut end = 0;
+
+    for annotation in annotations {
+        text.push_str(&program[end..annotation.span.start]);
+        end = annotation.span.end;
+
+        let source = &program[annotation.span.clone()];
+        let (fg, note) = match annotation.kind {
+            Kind::Counted { sum, .. } => (Color::Green, format!("[={}]", sum)),
+            Kind::Disabled => (Color::DarkGrey, "[off]".to_string()),
+            Kind::Do | Kind::Dont => (Color::Blue, String::new()),
+            Kind::NearMiss => (Color::Red, "[?]".to_string()),
+        };
+
+        if !color {
+            write!(text, "{}{}", source, note)
+        } else if annotation.kind == Kind::NearMiss {
+            write!(text, "{}{}", source.with(fg).underlined(), note.with(fg))
+        } else {
+            write!(text, "{}{}", source.with(fg), note.dark_yellow())
+        }
+        .unwrap();
+    }
+
+    text.push_str(&program[end..]);
+    text
+}
+
+/// Standalone HTML page showing `program` like [`to_text`] does with color,
+/// the product and running sum of every counted multiplication also in its
+/// tooltip.
+pub fn to_html(program: &str, annotations: &[Annotation]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Day 3</title>
+<style>
+pre { white-space: pre-wrap; word-break: break-all; }
+.counted { color: green; font-weight: bold; }
+.disabled { color: grey; }
+.conditional { color: blue; }
+.near-miss { color: red; text-decoration: underline wavy; }
+.sum { color: darkgoldenrod; }
+</style>
+</head>
+<body>
+",
+    );
+    writeln!(html, "<p>sum: {}</p>", total(annotations)).unwrap();
+    html.push_str("<pre>");
+
+    let mut end = 0;
+    for annotation in annotations {
+        html.push_str(&escape(&program[end..annotation.span.start]));
+        end = annotation.span.end;
+
+        let source = escape(&program[annotation.span.clone()]);
+        match annotation.kind {
+            Kind::Counted { product, sum } => write!(
+                html,
+                "<span class=\"counted\" title=\"{} (sum {})\">{}</span><sup class=\"sum\">{}</sup>",
+                product, sum, source, sum
+            ),
+            Kind::Disabled => write!(
+                html,
+                "<span class=\"disabled\" title=\"disabled\">{}</span>",
+                source
+            ),
+            Kind::Do | Kind::Dont => {
+                write!(html, "<span class=\"conditional\">{}</span>", source)
+            }
+            Kind::NearMiss => write!(
+                html,
+                "<span class=\"near-miss\" title=\"not an instruction\">{}</span>",
+                source
+            ),
+        }
+        .unwrap();
+    }
+
+    html.push_str(&escape(&program[end..]));
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day3::annotate::*;
+    use crate::inputs::Inputs;
+
+    fn example2() -> String {
+        Inputs::fixtures().load(3, "example2").unwrap()
+    }
+
+    #[test]
+    fn test_annotate() {
+        let example = example2();
+        let annotations = annotate(&example, true);
+
+        assert_eq!(
+            vec![
+                (1..9, Kind::Counted { product: 8, sum: 8 }),
+                (10..18, Kind::NearMiss),
+                (20..27, Kind::Dont),
+                (28..36, Kind::Disabled),
+                (37..47, Kind::NearMiss),
+                (48..57, Kind::Disabled),
+                (59..63, Kind::Do),
+                (
+                    64..72,
+                    Kind::Counted {
+                        product: 40,
+                        sum: 48
+                    }
+                ),
+            ],
+            annotations
+                .iter()
+                .map(|annotation| (annotation.span.clone(), annotation.kind))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(48, total(&annotations));
+
+        // part 1 counts every multiplication and ignores `do()` and `don't()`
+        let part1 = annotate(&example, false);
+        assert_eq!(161, total(&part1));
+        assert!(part1
+            .iter()
+            .all(|annotation| matches!(annotation.kind, Kind::Counted { .. } | Kind::NearMiss)));
+        assert_eq!(
+            "mul(2,3)[=6]don't()mul(4,5)[=26]",
+            to_text(
+                "mul(2,3)don't()mul(4,5)",
+                &annotate("mul(2,3)don't()mul(4,5)", false),
+                false
+            )
+        );
+
+        let near_misses = annotate("mul(1000,2) mul( 1,2) multiply do_", false);
+        assert_eq!(
+            vec![0..11, 12..21],
+            near_misses
+                .into_iter()
+                .map(|annotation| annotation.span)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let program = "mul(2,3)don't()mul(4,5)<mul(1000,1)";
+        let annotations = annotate(program, true);
+
+        assert_eq!(
+            "mul(2,3)[=6]don't()mul(4,5)[off]<mul(1000,1)[?]",
+            to_text(program, &annotations, false)
+        );
+
+        let html = to_html(program, &annotations);
+        assert!(html.contains("<p>sum: 6</p>"));
+        assert!(html.contains(
+            "<span class=\"counted\" title=\"6 (sum 6)\">mul(2,3)</span><sup class=\"sum\">6</sup>"
+        ));
+        assert!(html.contains("&lt;<span class=\"near-miss\""));
+    }
+}
@@ -0,0 +1,337 @@
+//! Interpreter of the instructions hidden in corrupted memory, with room for
+//! instructions the puzzle doesn't have.
+
+use std::ops::Range;
+
+use nom::{
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::char,
+    combinator::map_res,
+    multi::separated_list1,
+    sequence::delimited,
+    IResult,
+};
+
+/// Byte range of an instruction in the program.
+pub type Span = Range<usize>;
+
+/// Parser of the arguments of a registered instruction, including its name.
+pub type Parser = Box<dyn Fn(&str) -> IResult<&str, Vec<u64>> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(u64, u64),
+    Do,
+    Dont,
+    /// Instruction added with [`Interpreter::register`].
+    Custom {
+        name: &'static str,
+        args: Vec<u64>,
+    },
+}
+
+/// Instruction found in the program, `span` is where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spanned {
+    pub instruction: Instruction,
+    pub span: Span,
+}
+
+/// State of the machine running a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    /// Whether multiplications and registered instructions run, turned off
+    /// by `don't()` and back on by `do()`.
+    pub enabled: bool,
+    pub accumulator: u64,
+    /// Number of instructions executed so far.
+    pub counter: usize,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            enabled: true,
+            accumulator: 0,
+            counter: 0,
+        }
+    }
+}
+
+/// One instruction of a traced run, with the accumulator after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub instruction: Spanned,
+    /// False when the instruction was skipped because it was disabled.
+    pub executed: bool,
+    pub accumulator: u64,
+}
+
+struct Definition {
+    name: &'static str,
+    parser: Parser,
+    run: fn(&mut State, &[u64]),
+}
+
+/// Instruction set and the loop running it.
+pub struct Interpreter {
+    conditionals: bool,
+    custom: Vec<Definition>,
+}
+
+impl Default for Interpreter {
+    /// The instructions of part 2: `mul(a,b)`, `do()` and `don't()`.
+    fn default() -> Interpreter {
+        Interpreter {
+            conditionals: true,
+            custom: vec![],
+        }
+    }
+}
+
+impl Interpreter {
+    /// Treat `do()` and `don't()` as garbage, like part 1.
+    pub fn without_conditionals(mut self) -> Interpreter {
+        self.conditionals = false;
+        self
+    }
+
+    /// Add an instruction recognized by `parser`, which returns its
+    /// arguments, and executed by `run` while enabled. Registered
+    /// instructions are tried after the built-in ones, in order.
+    pub fn register(
+        mut self,
+        name: &'static str,
+        parser: impl Fn(&str) -> IResult<&str, Vec<u64>> + Send + Sync + 'static,
+        run: fn(&mut State, &[u64]),
+    ) -> Interpreter {
+        self.custom.push(Definition {
+            name,
+            parser: Box::new(parser),
+            run,
+        });
+        self
+    }
+
+    /// Instruction starting right at the beginning of `input`, with the
+    /// length it takes.
+    fn instruction(&self, input: &str) -> Option<(Instruction, usize)> {
+        let len = |rest: &str| input.len() - rest.len();
+
+        if let Ok((rest, args)) = call("mul", 2)(input) {
+            return Some((Instruction::Mul(args[0], args[1]), len(rest)));
+        }
+        if self.conditionals {
+            if let Ok((rest, _)) = tag::<_, _, ()>("do()")(input) {
+                return Some((Instruction::Do, len(rest)));
+            }
+            if let Ok((rest, _)) = tag::<_, _, ()>("don't()")(input) {
+                return Some((Instruction::Dont, len(rest)));
+            }
+        }
+
+        self.custom.iter().find_map(|definition| {
+            let (rest, args) = (definition.parser)(input).ok()?;
+            let name = definition.name;
+            Some((Instruction::Custom { name, args }, len(rest)))
+        })
+    }
+
+    /// Every instruction of `program`, skipping the garbage around them.
+    pub fn parse(&self, program: &str) -> Vec<Spanned> {
+        let mut instructions = vec![];
+        let mut start = 0;
+
+        while start < program.len() {
+            match self.instruction(&program[start..]) {
+                Some((instruction, len)) => {
+                    instructions.push(Spanned {
+                        instruction,
+                        span: start..start + len,
+                    });
+                    start += len;
+                }
+                None => {
+                    let c = program[start..].chars().next().expect("not at the end");
+                    start += c.len_utf8();
+                }
+            }
+        }
+
+        instructions
+    }
+
+    /// Run `instruction`, returning whether it was executed or skipped.
+    pub fn execute(&self, state: &mut State, instruction: &Instruction) -> bool {
+        match instruction {
+            Instruction::Do => state.enabled = true,
+            Instruction::Dont => state.enabled = false,
+            _ if !state.enabled => return false,
+            Instruction::Mul(a, b) => state.accumulator += a * b,
+            Instruction::Custom { name, args } => {
+                let definition = self
+                    .custom
+                    .iter()
+                    .find(|definition| definition.name == *name)
+                    .expect("custom instructions are registered");
+                (definition.run)(state, args);
+            }
+        }
+
+        state.counter += 1;
+        true
+    }
+
+    pub fn run(&self, program: &str) -> State {
+        let mut state = State::default();
+        for spanned in self.parse(program) {
+            self.execute(&mut state, &spanned.instruction);
+        }
+        state
+    }
+
+    /// Run `program` and record every instruction found in it.
+    pub fn trace(&self, program: &str) -> (State, Vec<Step>) {
+        let mut state = State::default();
+        let steps = self
+            .parse(program)
+            .into_iter()
+            .map(|instruction| Step {
+                executed: self.execute(&mut state, &instruction.instruction),
+                accumulator: state.accumulator,
+                instruction,
+            })
+            .collect();
+
+        (state, steps)
+    }
+}
+
+/// Number of 1 to 3 digits, the only ones instructions take.
+pub fn number(input: &str) -> IResult<&str, u64> {
+    map_res(
+        take_while_m_n(1, 3, |c: char| c.is_ascii_digit()),
+        str::parse,
+    )(input)
+}
+
+/// Parser of `name(a,b,...)` with exactly `arity` numbers, e.g. `mul(2,4)`
+/// for `call("mul", 2)` or `reset()` for `call("reset", 0)`.
+pub fn call(name: &'static str, arity: usize) -> impl Fn(&str) -> IResult<&str, Vec<u64>> {
+    move |input| {
+        let (input, _) = tag(name)(input)?;
+        if arity == 0 {
+            let (input, _) = tag("()")(input)?;
+            return Ok((input, vec![]));
+        }
+
+        let (rest, args) =
+            delimited(char('('), separated_list1(char(','), number), char(')'))(input)?;
+        if args.len() != arity {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Count,
+            )));
+        }
+
+        Ok((rest, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::sequence::preceded;
+
+    use crate::day3::interpreter::*;
+    use crate::inputs::Inputs;
+
+    fn example2() -> String {
+        Inputs::fixtures().load(3, "example2").unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let instructions = Interpreter::default().parse(&example2());
+
+        assert_eq!(
+            vec![
+                (Instruction::Mul(2, 4), 1..9),
+                (Instruction::Dont, 20..27),
+                (Instruction::Mul(5, 5), 28..36),
+                (Instruction::Mul(11, 8), 48..57),
+                (Instruction::Do, 59..63),
+                (Instruction::Mul(8, 5), 64..72),
+            ],
+            instructions
+                .into_iter()
+                .map(|spanned| (spanned.instruction, spanned.span))
+                .collect::<Vec<_>>()
+        );
+
+        // too many digits, spaces and a missing argument
+        assert!(Interpreter::default()
+            .parse("mul(1000,2) mul( 1,2) mul(4) mul(1,2,3)")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_trace() {
+        let (state, steps) = Interpreter::default().trace(&example2());
+
+        assert_eq!(
+            State {
+                enabled: true,
+                accumulator: 48,
+                counter: 4
+            },
+            state
+        );
+        assert_eq!(
+            vec![
+                (true, 8),
+                (true, 8),
+                (false, 8),
+                (false, 8),
+                (true, 8),
+                (true, 48)
+            ],
+            steps
+                .iter()
+                .map(|step| (step.executed, step.accumulator))
+                .collect::<Vec<_>>()
+        );
+
+        let part1 = Interpreter::default().without_conditionals();
+        assert_eq!(161, part1.run(&example2()).accumulator);
+    }
+
+    #[test]
+    fn test_register() {
+        let interpreter = Interpreter::default()
+            .register("add", call("add", 2), |state, args| {
+                state.accumulator += args[0] + args[1]
+            })
+            .register("reset", call("reset", 0), |state, _| state.accumulator = 0)
+            .register(
+                "square",
+                |input| {
+                    let (rest, n) = preceded(tag("sq"), number)(input)?;
+                    Ok((rest, vec![n]))
+                },
+                |state, args| state.accumulator += args[0] * args[0],
+            );
+
+        let (state, steps) =
+            interpreter.trace("mul(2,3)reset()add(1,2)don't()add(5,5)do()sq4add(1)");
+        assert_eq!(19, state.accumulator);
+        assert_eq!(6, state.counter);
+        assert_eq!(
+            Instruction::Custom {
+                name: "square",
+                args: vec![4]
+            },
+            steps[6].instruction.instruction
+        );
+        assert_eq!(steps.len(), 7);
+    }
+}
@@ -0,0 +1,245 @@
+//! Scanner finding `mul(a,b)`, `do()` and `don't()` in corrupted memory one
+//! byte at a time, so programs of any size are read in constant memory.
+
+use std::io::{self, BufRead, ErrorKind};
+
+use crate::day3::interpreter::{Instruction, Spanned};
+
+const MUL: &[u8] = b"mul(";
+const DO: &[u8] = b"do()";
+const DONT: &[u8] = b"don't()";
+
+/// How much of an instruction the last bytes matched. Every instruction
+/// starts with a byte found nowhere else in it, so after a mismatch a new
+/// one can only start on the mismatched byte.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Match {
+    None,
+    /// This many bytes of `mul(`.
+    Mul(usize),
+    First {
+        value: u64,
+        digits: u32,
+    },
+    Second {
+        a: u64,
+        value: u64,
+        digits: u32,
+    },
+    /// This many bytes of `do()`.
+    Do(usize),
+    /// This many bytes of `don't()`.
+    Dont(usize),
+}
+
+/// Byte by byte matcher of the instructions of the puzzle, without
+/// allocating.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    state: Match,
+    /// Offset of the next byte.
+    offset: usize,
+    /// Offset of the instruction being matched.
+    start: usize,
+}
+
+impl Default for Scanner {
+    fn default() -> Scanner {
+        Scanner {
+            state: Match::None,
+            offset: 0,
+            start: 0,
+        }
+    }
+}
+
+impl Scanner {
+    /// Feed the next byte of the program, returning the instruction it
+    /// completes if any.
+    pub fn push(&mut self, byte: u8) -> Option<Spanned> {
+        let digit = |value: u64| value * 10 + (byte - b'0') as u64;
+
+        let (state, found) = match (self.state, byte) {
+            (Match::Mul(n), _) if MUL[n] == byte => match n + 1 {
+                n if n == MUL.len() => (
+                    Match::First {
+                        value: 0,
+                        digits: 0,
+                    },
+                    None,
+                ),
+                n => (Match::Mul(n), None),
+            },
+            (Match::First { value, digits }, b'0'..=b'9') if digits < 3 => (
+                Match::First {
+                    value: digit(value),
+                    digits: digits + 1,
+                },
+                None,
+            ),
+            (Match::First { value, digits }, b',') if digits > 0 => (
+                Match::Second {
+                    a: value,
+                    value: 0,
+                    digits: 0,
+                },
+                None,
+            ),
+            (Match::Second { a, value, digits }, b'0'..=b'9') if digits < 3 => (
+                Match::Second {
+                    a,
+                    value: digit(value),
+                    digits: digits + 1,
+                },
+                None,
+            ),
+            (Match::Second { a, value, digits }, b')') if digits > 0 => {
+                (Match::None, Some(Instruction::Mul(a, value)))
+            }
+            (Match::Do(n), _) if DO[n] == byte => match n + 1 {
+                n if n == DO.len() => (Match::None, Some(Instruction::Do)),
+                n => (Match::Do(n), None),
+            },
+            (Match::Do(2), b'n') => (Match::Dont(3), None),
+            (Match::Dont(n), _) if DONT[n] == byte => match n + 1 {
+                n if n == DONT.len() => (Match::None, Some(Instruction::Dont)),
+                n => (Match::Dont(n), None),
+            },
+            (_, b'm') => {
+                self.start = self.offset;
+                (Match::Mul(1), None)
+            }
+            (_, b'd') => {
+                self.start = self.offset;
+                (Match::Do(1), None)
+            }
+            _ => (Match::None, None),
+        };
+
+        self.state = state;
+        self.offset += 1;
+
+        found.map(|instruction| Spanned {
+            instruction,
+            span: self.start..self.offset,
+        })
+    }
+}
+
+/// Instructions of `program`, in order.
+pub fn scan(program: &str) -> impl Iterator<Item = Spanned> + '_ {
+    let mut scanner = Scanner::default();
+    program.bytes().filter_map(move |byte| scanner.push(byte))
+}
+
+/// Instructions of a program read from `reader` as they are found, holding
+/// no more than the reader's buffer.
+pub struct Instructions<R> {
+    reader: R,
+    scanner: Scanner,
+}
+
+impl<R: BufRead> Instructions<R> {
+    pub fn new(reader: R) -> Instructions<R> {
+        Instructions {
+            reader,
+            scanner: Scanner::default(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Instructions<R> {
+    type Item = io::Result<Spanned>;
+
+    fn next(&mut self) -> Option<io::Result<Spanned>> {
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok([]) => return None,
+                Ok(buf) => buf,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let mut consumed = buf.len();
+            let mut found = None;
+            for (i, &byte) in buf.iter().enumerate() {
+                if let Some(spanned) = self.scanner.push(byte) {
+                    (consumed, found) = (i + 1, Some(spanned));
+                    break;
+                }
+            }
+
+            self.reader.consume(consumed);
+            if let Some(spanned) = found {
+                return Some(Ok(spanned));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+
+    use proptest::prelude::*;
+
+    use crate::day3::interpreter::Interpreter;
+    use crate::day3::scanner::*;
+    use crate::inputs::Inputs;
+
+    fn example2() -> String {
+        Inputs::fixtures().load(3, "example2").unwrap()
+    }
+
+    #[test]
+    fn test_scan() {
+        let example = example2();
+        assert_eq!(
+            Interpreter::default().parse(&example),
+            scan(&example).collect::<Vec<_>>()
+        );
+
+        let near_misses = "mul(1000,2) mul( 1,2) mul(4) mul(1,2,3) do_() don't( mul(4*";
+        assert_eq!(0, scan(near_misses).count());
+
+        // a mismatched byte can start the next instruction
+        assert_eq!(
+            vec![
+                (Instruction::Mul(1, 2), 4..12),
+                (Instruction::Do, 13..17),
+                (Instruction::Dont, 19..26)
+            ],
+            scan("mulmmul(1,2)ddo()dodon't()")
+                .map(|spanned| (spanned.instruction, spanned.span))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_instructions() {
+        // a tiny buffer splits instructions between reads
+        let example = example2();
+        let reader = BufReader::with_capacity(3, example.as_bytes());
+        let instructions: Vec<Spanned> = Instructions::new(reader)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(scan(&example).collect::<Vec<_>>(), instructions);
+
+        // garbage much bigger than the buffer
+        let garbage = io::repeat(b'x').take(1 << 20);
+        let reader = BufReader::new(garbage.chain(example.as_bytes()));
+        let last = Instructions::new(reader).last().unwrap().unwrap();
+        assert_eq!(Instruction::Mul(8, 5), last.instruction);
+        assert_eq!((1 << 20) + 64..(1 << 20) + 72, last.span);
+    }
+
+    proptest! {
+        #[test]
+        fn test_same_as_interpreter(program in "[mul(do'nt),0-9x]{0,200}") {
+            prop_assert_eq!(
+                Interpreter::default().parse(&program),
+                scan(&program).collect::<Vec<_>>()
+            );
+        }
+    }
+}
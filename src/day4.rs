@@ -1,29 +1,16 @@
-use std::collections::HashMap;
+use aoc_runner_derive::{aoc, aoc_generator};
 
-use aoc_runner_derive::aoc;
+use crate::error::{ParseError, SolveError};
+use crate::grid::{Grid, Point, ALL_DIRECTIONS};
+use crate::solution::{Answer, Solution};
 
-#[derive(Hash, Eq, PartialEq, Debug)]
-struct XY {
-    x: i32,
-    y: i32,
+#[aoc_generator(day4)]
+pub fn parse_input(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(4, input, |c| c)
 }
 
 #[aoc(day4, part1)]
-pub fn xmas(input: &str) -> u64 {
-    let mut matrix = HashMap::<XY, char>::new();
-    // create matrix
-    input.lines().enumerate().for_each(|(row, line)| {
-        line.split("").enumerate().for_each(|(col, char)| {
-            if let Some(chr) = char.chars().next() {
-                let key = XY {
-                    x: row as i32,
-                    y: col as i32,
-                };
-                matrix.insert(key, chr);
-            }
-        });
-    });
-
+pub fn xmas(matrix: &Grid<char>) -> u64 {
     let mut res = 0;
 
     matrix.iter().for_each(|(xy, chr)| {
@@ -31,28 +18,10 @@ pub fn xmas(input: &str) -> u64 {
             return;
         }
 
-        let directions: Vec<(i32, i32)> = vec![
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-
-        let default = &' ';
-
-        directions.iter().for_each(|direction| {
-            let m_pos = &offset(xy, *direction, 1);
-            let a_pos = &offset(xy, *direction, 2);
-            let s_pos = &offset(xy, *direction, 3);
+        ALL_DIRECTIONS.iter().for_each(|direction| {
+            let word = matrix.ray(xy, *direction).skip(1).take(3).map(|(_, c)| *c);
 
-            if matrix.get(m_pos).unwrap_or(default) == &'M'
-                && matrix.get(a_pos).unwrap_or(default) == &'A'
-                && matrix.get(s_pos).unwrap_or(default) == &'S'
-            {
+            if word.eq("MAS".chars()) {
                 res += 1;
             }
         });
@@ -61,29 +30,8 @@ pub fn xmas(input: &str) -> u64 {
     res
 }
 
-fn offset(xy: &XY, direction: (i32, i32), mul: i32) -> XY {
-    XY {
-        x: xy.x + (direction.0 * mul),
-        y: xy.y + (direction.1 * mul),
-    }
-}
-
 #[aoc(day4, part2)]
-pub fn x_mas(input: &str) -> u64 {
-    let mut matrix = HashMap::<XY, char>::new();
-    // create matrix
-    input.lines().enumerate().for_each(|(row, line)| {
-        line.split("").enumerate().for_each(|(col, char)| {
-            if let Some(chr) = char.chars().next() {
-                let key = XY {
-                    x: row as i32,
-                    y: col as i32,
-                };
-                matrix.insert(key, chr);
-            }
-        });
-    });
-
+pub fn x_mas(matrix: &Grid<char>) -> u64 {
     let mut res = 0;
 
     matrix.iter().for_each(|(xy, chr)| {
@@ -92,22 +40,18 @@ pub fn x_mas(input: &str) -> u64 {
         }
 
         // clockwise
-        let pairs = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
+        let pairs = [
+            Point::new(-1, -1),
+            Point::new(1, -1),
+            Point::new(1, 1),
+            Point::new(-1, 1),
+        ];
 
-        let phrase: Vec<char> = pairs
+        let s: String = pairs
             .iter()
-            .map(|pair| {
-                let m = XY {
-                    x: xy.x + pair.0,
-                    y: xy.y + pair.1,
-                };
-
-                *matrix.get(&m).unwrap_or(&' ')
-            })
+            .map(|pair| *matrix.get(xy + *pair).unwrap_or(&' '))
             .collect();
 
-        let s = String::from_iter(phrase);
-
         if s == "MMSS" || s == "SMMS" || s == "SSMM" || s == "MSSM" {
             res += 1;
         }
@@ -116,24 +60,36 @@ pub fn x_mas(input: &str) -> u64 {
     res
 }
 
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+
+    type Input = Grid<char>;
+
+    fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Grid<char>) -> Result<Answer, SolveError> {
+        Ok(xmas(input))
+    }
+
+    fn part2(input: &Grid<char>) -> Result<Answer, SolveError> {
+        Ok(x_mas(input))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day4::{x_mas, xmas};
+    use crate::day4::{parse_input, x_mas, xmas};
+    use crate::inputs::Inputs;
 
     #[test]
     fn test_matrix() {
-        let input = "MMMSXXMASM
-MSAMXMSMSA
-AMXSXMAAMM
-MSAMASMSMX
-XMASAMXAMM
-XXAMMXXAMA
-SMSMSASXSS
-SAXAMASAAA
-MAMMMXMMMM
-MXMXAXMASX";
-
-        assert_eq!(18, xmas(input));
+        let input = Inputs::fixtures().load(4, "example").unwrap();
+
+        assert_eq!(18, xmas(&parse_input(&input).unwrap()));
     }
 
     #[test]
@@ -149,6 +105,6 @@ S.S.S.S.S.
 M.M.M.M.M.
 ..........";
 
-        assert_eq!(9, x_mas(input));
+        assert_eq!(9, x_mas(&parse_input(input).unwrap()));
     }
 }
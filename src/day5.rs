@@ -1,51 +1,76 @@
 use aoc_runner_derive::aoc;
 
+use crate::error::{number, ParseError, SolveError};
+use crate::parse::fields;
+use crate::solution::{Answer, Solution};
+
+pub mod dot;
+pub mod graph;
+
+pub use dot::{to_dot, DotOptions};
+pub use graph::{Cycle, PrecedenceGraph};
+
 pub struct Input {
     pages: Vec<Vec<u64>>,
-    order: Vec<(u64, u64)>,
+    graph: PrecedenceGraph,
 }
 
-#[aoc_generator(day5)]
-fn input_generator(input: &str) -> Input {
-    let (order, mut pages): (Vec<&str>, Vec<&str>) =
-        input.lines().partition(|line| line.contains("|"));
-
-    pages = pages[1..].to_vec();
+impl Input {
+    pub fn updates(&self) -> &[Vec<u64>] {
+        &self.pages
+    }
 
-    let pages_nums = pages
-        .iter()
-        .map(|page| {
-            page.split(',')
-                .map(|n| n.parse::<u64>().expect("page parsed as number"))
-                .collect::<Vec<u64>>()
-        })
-        .collect::<Vec<Vec<u64>>>();
+    pub fn graph(&self) -> &PrecedenceGraph {
+        &self.graph
+    }
+}
 
+#[aoc_generator(day5)]
+fn input_generator(input: &str) -> Result<Input, ParseError> {
     let mut order_pairs = Vec::<(u64, u64)>::new();
+    let mut pages_nums = Vec::<Vec<u64>>::new();
+
+    let mut lines = input.lines().enumerate();
 
-    order.iter().for_each(|o| {
-        let (u, v) = o.split_once('|').expect("order split by |");
-        let prev_page: u64 = u.parse().expect("parsed prev page");
-        let next_page: u64 = v.parse().expect("parsed next page");
+    // ordering rules until the first blank line
+    for (i, line) in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+
+        let (u, v) = line.split_once('|').ok_or(ParseError::UnexpectedEnd {
+            day: 5,
+            line: i + 1,
+            column: line.len() + 1,
+            expected: "`|` between ordering rule pages",
+        })?;
+        let prev_page: u64 = number(5, i + 1, 1, u)?;
+        let next_page: u64 = number(5, i + 1, u.len() + 2, v)?;
 
         order_pairs.push((prev_page, next_page));
-    });
+    }
 
-    Input {
-        pages: pages_nums,
-        order: order_pairs,
+    // then one update per line
+    for (i, line) in lines {
+        let page = fields(line, ',')
+            .map(|(column, n)| number::<u64>(5, i + 1, column, n))
+            .collect::<Result<Vec<u64>, ParseError>>()?;
+
+        pages_nums.push(page);
     }
+
+    Ok(Input {
+        pages: pages_nums,
+        graph: PrecedenceGraph::new(&order_pairs),
+    })
 }
 
 #[aoc(day5, part1)]
 pub fn print_sum(input: &Input) -> u64 {
-    let pages_nums = input.pages.clone();
-    let order_pairs = input.order.clone();
-
     let mut sum: u64 = 0;
 
-    for page in pages_nums {
-        if is_valid_order(&order_pairs, &page) {
+    for page in &input.pages {
+        if input.graph.is_valid_order(page) {
             // If the page numbers are in valid order, add the middle number
             sum += page[page.len() / 2];
         }
@@ -54,111 +79,111 @@ pub fn print_sum(input: &Input) -> u64 {
     sum
 }
 
-fn is_valid_order(order: &Vec<(u64, u64)>, numbers: &[u64]) -> bool {
-    order.iter().all(|(prev, next)| {
-        let prev_pos = numbers.iter().position(|&x| x == *prev);
-        let next_pos = numbers.iter().position(|&x| x == *next);
-
-        if prev_pos.is_none() || next_pos.is_none() {
-            return true;
-        }
-
-        prev_pos.unwrap() < next_pos.unwrap()
-    })
-}
-
 #[aoc(day5, part2)]
-fn correct(input: &Input) -> u64 {
-    let pages_nums = input.pages.clone();
-    let order_pairs = input.order.clone();
-
+fn correct(input: &Input) -> Result<u64, SolveError> {
     let mut sum: u64 = 0;
 
-    for page in pages_nums {
-        if !is_valid_order(&order_pairs, &page) {
+    for page in &input.pages {
+        if !input.graph.is_valid_order(page) {
             // If the page numbers are invalid order, fix page and sum middle
-            // go from right to left
-
-            println!("incorrect page {:?}", page);
-
-            let fixed = fix_page(page, &order_pairs);
+            let fixed = fix_page(page, &input.graph).map_err(|cycle| SolveError::Unsolvable {
+                day: 5,
+                reason: cycle.to_string(),
+            })?;
 
             sum += fixed[fixed.len() / 2];
         }
     }
 
-    sum
+    Ok(sum)
 }
 
-fn fix_page(page: Vec<u64>, order: &Vec<(u64, u64)>) -> Vec<u64> {
-    let mut fixed = page.clone();
+fn fix_page(page: &[u64], graph: &PrecedenceGraph) -> Result<Vec<u64>, Cycle> {
+    graph.sort(page)
+}
 
-    for i in (0..page.len()).rev() {
-        for j in (0..i).rev() {
-            let prev = fixed[i];
-            let next = fixed[j];
+pub struct Day5;
 
-            if order.iter().any(|(p, n)| *p == prev && *n == next) {
-                fixed[i] = next;
-                fixed[j] = prev;
-            }
-        }
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Input = Input;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Input) -> Result<Answer, SolveError> {
+        Ok(print_sum(input))
     }
 
-    fixed
+    fn part2(input: &Input) -> Result<Answer, SolveError> {
+        correct(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day5::*;
+    use crate::inputs::Inputs;
 
-    static INPUT: &str = "47|53
-97|13
-97|61
-97|47
-75|29
-61|13
-75|53
-29|13
-97|29
-53|29
-61|53
-97|53
-61|29
-47|13
-75|47
-97|75
-47|61
-75|61
-47|29
-75|13
-53|13
-
-75,47,61,53,29
-97,61,53,29,13
-75,29,13
-75,97,47,61,53
-61,13,29
-97,13,75,29,47";
+    fn example() -> String {
+        Inputs::fixtures().load(5, "example").unwrap()
+    }
 
     #[test]
     fn fix_page_test() {
-        let input = input_generator(INPUT);
+        let input = input_generator(&example()).unwrap();
         assert_eq!(
-            vec![97, 75, 47, 61, 53],
-            fix_page(vec![75, 97, 47, 61, 53], &input.order)
+            Ok(vec![97, 75, 47, 61, 53]),
+            fix_page(&[75, 97, 47, 61, 53], &input.graph)
         );
 
-        assert_eq!(vec![61, 29, 13], fix_page(vec![61, 13, 29], &input.order));
+        assert_eq!(Ok(vec![61, 29, 13]), fix_page(&[61, 13, 29], &input.graph));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 5,
+                line: 2,
+                column: 4,
+                token: "1x".to_string(),
+            },
+            input_generator("47|53\n97|1x\n\n75,47").err().unwrap()
+        );
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 5,
+                line: 4,
+                column: 4,
+                token: "".to_string(),
+            },
+            input_generator("47|53\n\n75,47\n75,,47").err().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let input = input_generator("1|2\n2|3\n3|1\n\n3,2,1").unwrap();
+
+        assert_eq!(
+            Err(SolveError::Unsolvable {
+                day: 5,
+                reason: "rules form a cycle 1 -> 2 -> 3 -> 1".to_string()
+            }),
+            correct(&input)
+        );
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(143, print_sum(&input_generator(INPUT)));
+        assert_eq!(143, print_sum(&input_generator(&example()).unwrap()));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(123, correct(&input_generator(INPUT)));
+        assert_eq!(Ok(123), correct(&input_generator(&example()).unwrap()));
     }
 }
@@ -0,0 +1,131 @@
+use std::{collections::HashSet, fmt::Write};
+
+use super::{fix_page, PrecedenceGraph};
+
+/// What [`to_dot`] renders besides the rules.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DotOptions<'a> {
+    /// Render only the pages of this update and the rules between them.
+    pub update: Option<&'a [u64]>,
+    /// Color the rules broken by `update` red, nothing to color without
+    /// one.
+    pub highlight_violations: bool,
+    /// Label the pages of `update` with their position in the order
+    /// repaired by `fix_page`, ignored without one.
+    pub annotate_fix: bool,
+}
+
+/// Graphviz DOT source of the ordering rules, like `graph.dot`.
+pub fn to_dot(graph: &PrecedenceGraph, options: &DotOptions) -> String {
+    let update = options.update.unwrap_or(&[]);
+    let graph = match options.update {
+        Some(update) => graph.induced(update),
+        None => graph.clone(),
+    };
+
+    let violations: HashSet<(u64, u64)> = if options.highlight_violations {
+        graph.violations(update).into_iter().collect()
+    } else {
+        HashSet::new()
+    };
+
+    let mut dot = String::from("digraph {\n");
+
+    if options.annotate_fix && options.update.is_some() {
+        match fix_page(update, &graph) {
+            Ok(fixed) => {
+                let order: Vec<String> = fixed.iter().map(|p| p.to_string()).collect();
+                writeln!(dot, "    label=\"fixed: {}\";", order.join(",")).unwrap();
+                for (i, page) in fixed.iter().enumerate() {
+                    writeln!(dot, "    {} [label=\"{} (#{})\"];", page, page, i + 1).unwrap();
+                }
+            }
+            Err(cycle) => writeln!(dot, "    label=\"{}\";", cycle).unwrap(),
+        }
+    } else {
+        // pages without rules would be missing otherwise
+        for page in update {
+            writeln!(dot, "    {};", page).unwrap();
+        }
+    }
+
+    let mut edges: Vec<(u64, u64)> = graph.edges().collect();
+    edges.sort_unstable();
+
+    for (prev, next) in edges {
+        if violations.contains(&(prev, next)) {
+            writeln!(dot, "    {} -> {} [color=red];", prev, next).unwrap();
+        } else {
+            writeln!(dot, "    {} -> {};", prev, next).unwrap();
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day5::dot::*;
+
+    #[test]
+    fn test_full_graph() {
+        let graph = PrecedenceGraph::new(&[(47, 53), (97, 13), (47, 13)]);
+
+        assert_eq!(
+            "digraph {
+    47 -> 13;
+    47 -> 53;
+    97 -> 13;
+}
+",
+            to_dot(&graph, &DotOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let graph = PrecedenceGraph::new(&[(97, 75), (75, 47), (97, 47), (47, 13)]);
+        let update = [75, 97, 47];
+
+        assert_eq!(
+            "digraph {
+    75;
+    97;
+    47;
+    75 -> 47;
+    97 -> 47;
+    97 -> 75;
+}
+",
+            to_dot(
+                &graph,
+                &DotOptions {
+                    update: Some(&update),
+                    ..DotOptions::default()
+                }
+            )
+        );
+
+        assert_eq!(
+            "digraph {
+    label=\"fixed: 97,75,47\";
+    97 [label=\"97 (#1)\"];
+    75 [label=\"75 (#2)\"];
+    47 [label=\"47 (#3)\"];
+    75 -> 47;
+    97 -> 47;
+    97 -> 75 [color=red];
+}
+",
+            to_dot(
+                &graph,
+                &DotOptions {
+                    update: Some(&update),
+                    highlight_violations: true,
+                    annotate_fix: true,
+                }
+            )
+        );
+    }
+}
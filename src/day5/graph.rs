@@ -0,0 +1,228 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+
+/// Page ordering rules as a directed graph, with an edge `a -> b` for every
+/// rule `a|b`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecedenceGraph {
+    after: HashMap<u64, HashSet<u64>>,
+}
+
+/// Pages whose rules require each of them to come before the next one, and
+/// the last one before the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle(pub Vec<u64>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rules form a cycle ")?;
+        for page in &self.0 {
+            write!(f, "{} -> ", page)?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for Cycle {}
+
+impl PrecedenceGraph {
+    pub fn new(rules: &[(u64, u64)]) -> PrecedenceGraph {
+        let mut after = HashMap::<u64, HashSet<u64>>::new();
+        for &(prev, next) in rules {
+            after.entry(prev).or_default().insert(next);
+        }
+        PrecedenceGraph { after }
+    }
+
+    /// Whether a rule says `prev` must come before `next`.
+    pub fn precedes(&self, prev: u64, next: u64) -> bool {
+        self.after
+            .get(&prev)
+            .is_some_and(|pages| pages.contains(&next))
+    }
+
+    /// Pages that must come after `page`.
+    pub fn successors(&self, page: u64) -> impl Iterator<Item = u64> + '_ {
+        self.after.get(&page).into_iter().flatten().copied()
+    }
+
+    /// Every rule as a `(prev, next)` edge, in no particular order.
+    pub fn edges(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.after
+            .iter()
+            .flat_map(|(&prev, pages)| pages.iter().map(move |&next| (prev, next)))
+    }
+
+    /// Subgraph with only the rules between pages of `update`.
+    pub fn induced(&self, update: &[u64]) -> PrecedenceGraph {
+        let pages: HashSet<u64> = update.iter().copied().collect();
+        let mut after = HashMap::<u64, HashSet<u64>>::new();
+
+        for &page in &pages {
+            let next: HashSet<u64> = self
+                .successors(page)
+                .filter(|next| pages.contains(next))
+                .collect();
+            if !next.is_empty() {
+                after.insert(page, next);
+            }
+        }
+
+        PrecedenceGraph { after }
+    }
+
+    /// Rules broken by `update`, as `(prev, next)` pairs where `next` is
+    /// printed before `prev`.
+    pub fn violations(&self, update: &[u64]) -> Vec<(u64, u64)> {
+        let positions: HashMap<u64, usize> =
+            update.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+
+        let mut violations = vec![];
+        for (i, &page) in update.iter().enumerate() {
+            for next in self.successors(page) {
+                if let Some(&j) = positions.get(&next).filter(|&&j| j < i) {
+                    violations.push((i, j));
+                }
+            }
+        }
+
+        violations.sort_unstable();
+        violations
+            .into_iter()
+            .map(|(i, j)| (update[i], update[j]))
+            .collect()
+    }
+
+    /// Whether `update` follows every rule, in O(n + e) for n pages and the
+    /// e rules starting at them.
+    pub fn is_valid_order(&self, update: &[u64]) -> bool {
+        let positions: HashMap<u64, usize> =
+            update.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+
+        update.iter().enumerate().all(|(i, &page)| {
+            self.successors(page)
+                .all(|next| positions.get(&next).is_none_or(|&j| j > i))
+        })
+    }
+
+    /// Reorder `update` to follow the rules with Kahn's algorithm. Among
+    /// the pages ready to be placed the earliest one in `update` goes first,
+    /// so pages not constrained by each other keep their relative order.
+    pub fn sort(&self, update: &[u64]) -> Result<Vec<u64>, Cycle> {
+        let graph = self.induced(update);
+        let positions: HashMap<u64, usize> =
+            update.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+
+        let mut in_degree: HashMap<u64, usize> = update.iter().map(|&p| (p, 0)).collect();
+        for (_, next) in graph.edges() {
+            *in_degree.get_mut(&next).expect("induced edge") += 1;
+        }
+
+        let mut ready: BinaryHeap<Reverse<usize>> = update
+            .iter()
+            .enumerate()
+            .filter(|(_, page)| in_degree[page] == 0)
+            .map(|(i, _)| Reverse(i))
+            .collect();
+        let mut sorted = Vec::with_capacity(update.len());
+
+        while let Some(Reverse(i)) = ready.pop() {
+            let page = update[i];
+            sorted.push(page);
+
+            for next in graph.successors(page) {
+                let degree = in_degree.get_mut(&next).expect("induced page");
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(Reverse(positions[&next]));
+                }
+            }
+        }
+
+        if sorted.len() < in_degree.len() {
+            return Err(graph.find_cycle(&in_degree));
+        }
+
+        Ok(sorted)
+    }
+
+    /// Cycle among the pages Kahn's algorithm couldn't sort. Each of them has
+    /// an unsorted predecessor, so walking predecessors must loop.
+    fn find_cycle(&self, in_degree: &HashMap<u64, usize>) -> Cycle {
+        let remaining: HashSet<u64> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree > 0)
+            .map(|(&page, _)| page)
+            .collect();
+
+        let mut before = HashMap::<u64, u64>::new();
+        for (prev, next) in self.edges() {
+            if remaining.contains(&prev) && remaining.contains(&next) {
+                before
+                    .entry(next)
+                    .and_modify(|p: &mut u64| *p = (*p).min(prev))
+                    .or_insert(prev);
+            }
+        }
+
+        let mut path = vec![*remaining.iter().min().expect("unsorted pages")];
+        let mut seen = HashMap::from([(path[0], 0)]);
+        loop {
+            let prev = before[path.last().expect("non-empty path")];
+            if let Some(&start) = seen.get(&prev) {
+                let mut cycle = path.split_off(start);
+                cycle.reverse();
+                // start from the smallest page, like the search
+                let smallest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+                cycle.rotate_left(smallest);
+                return Cycle(cycle);
+            }
+            seen.insert(prev, path.len());
+            path.push(prev);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day5::graph::*;
+
+    #[test]
+    fn test_valid_order() {
+        let graph = PrecedenceGraph::new(&[(1, 2), (2, 3), (1, 3), (4, 5)]);
+
+        assert!(graph.is_valid_order(&[1, 2, 3]));
+        assert!(graph.is_valid_order(&[1, 5, 3]));
+        assert!(!graph.is_valid_order(&[2, 1, 3]));
+        assert_eq!(vec![(2, 3), (1, 3), (1, 2)], graph.violations(&[3, 2, 1]));
+    }
+
+    #[test]
+    fn test_sort() {
+        let graph = PrecedenceGraph::new(&[(1, 2), (2, 3), (4, 5)]);
+
+        assert_eq!(Ok(vec![1, 2, 3]), graph.sort(&[3, 2, 1]));
+        assert_eq!(Ok(vec![4, 5, 1, 2]), graph.sort(&[5, 2, 4, 1]));
+        assert_eq!(Ok(vec![]), graph.sort(&[]));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let graph = PrecedenceGraph::new(&[(1, 2), (2, 3), (3, 1), (3, 4), (5, 1)]);
+
+        assert_eq!(Err(Cycle(vec![1, 2, 3])), graph.sort(&[4, 3, 2, 1, 5]));
+        // without page 3 the cycle is broken
+        assert_eq!(Ok(vec![4, 5, 1, 2]), graph.sort(&[4, 2, 1, 5]));
+        assert_eq!(
+            "rules form a cycle 1 -> 2 -> 3 -> 1",
+            Cycle(vec![1, 2, 3]).to_string()
+        );
+    }
+}
@@ -1,43 +1,52 @@
 use aoc_runner_derive::aoc;
 use std::{
     collections::{HashMap, HashSet},
+    env, fmt,
     ops::{Add, AddAssign},
+    sync::atomic::{self, AtomicUsize},
+    thread,
 };
 
+use crate::error::{ParseError, SolveError};
+use crate::grid::{Grid, Point};
+use crate::solution::{Answer, Part, Solution, Variant};
+
+pub mod jump;
+pub mod legend;
+pub mod visualize;
+
+pub use jump::JumpTable;
+pub use legend::{Legend, Tile};
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-enum Entity {
+pub enum Entity {
     Blank,
     Obstacle,
+    /// Can only be walked onto facing this way, an obstacle otherwise.
+    OneWay(Direction),
+    /// Walking onto it moves the guard to the other end of the portal, still
+    /// facing the same way.
+    Portal,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Pos {
-    x: i64,
-    y: i64,
+impl Entity {
+    /// Whether a guard facing `direction` turns in front of it.
+    pub fn blocks(self, direction: Direction) -> bool {
+        match self {
+            Entity::Obstacle => true,
+            Entity::OneWay(way) => way != direction,
+            Entity::Blank | Entity::Portal => false,
+        }
+    }
 }
 
+type Pos = Point;
+
 impl Add<Direction> for Pos {
     type Output = Pos;
 
     fn add(self, direction: Direction) -> Pos {
-        match direction {
-            Direction::Up => Pos {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Direction::Down => Pos {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Direction::Left => Pos {
-                x: self.x - 1,
-                y: self.y,
-            },
-            Direction::Right => Pos {
-                x: self.x + 1,
-                y: self.y,
-            },
-        }
+        self + direction.delta()
     }
 }
 
@@ -48,341 +57,483 @@ impl AddAssign<Direction> for Pos {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum Direction {
+pub enum Direction {
     Up,
     Right,
     Down,
     Left,
 }
 
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    fn delta(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+
+    /// Position of the direction in clockwise order, starting up.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    /// Arrow drawn for a guard facing this way, as on the map.
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
-enum SimulationResult {
+pub enum SimulationResult {
     Loop,
     Exit,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Input {
-    map: HashMap<Pos, Entity>,
-    guard: Pos,
+    map: Grid<Entity>,
+    guards: Vec<(Pos, Direction)>,
+    /// Other end of the portal on each portal cell.
+    portals: HashMap<Pos, Pos>,
 }
 
-#[aoc_generator(day6)]
-fn parse_input(input: &str) -> Input {
-    let mut result = Input {
-        map: HashMap::new(),
-        guard: Pos { x: 0, y: 0 },
-    };
+impl Input {
+    /// Starting position and direction of every guard, in reading order.
+    pub fn guards(&self) -> &[(Pos, Direction)] {
+        &self.guards
+    }
 
-    input.lines().enumerate().for_each(|(y, line)| {
-        line.chars().enumerate().for_each(|(x, c)| {
-            let pos = Pos {
-                x: x as i64,
-                y: y as i64,
-            };
-            let entity = match c {
-                '.' => Entity::Blank,
-                '#' => Entity::Obstacle,
-                '^' => {
-                    result.guard = pos;
-                    Entity::Blank
-                }
-                _ => panic!("Invalid entity {:?}", c),
-            };
-            result.map.insert(pos, entity);
-        });
-    });
+    /// Where a guard walking onto the portal on `pos` comes out.
+    pub fn portal_exit(&self, pos: Pos) -> Option<Pos> {
+        self.portals.get(&pos).copied()
+    }
+
+    /// Whether a guard starts on `pos`, no obstacle can go there.
+    fn is_start(&self, pos: Pos) -> bool {
+        self.guards.iter().any(|&(guard, _)| guard == pos)
+    }
+
+    pub fn width(&self) -> usize {
+        self.map.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.height()
+    }
+}
 
-    result
+#[aoc_generator(day6)]
+pub(crate) fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Legend::default().parse(input)
 }
 
 #[aoc(day6, part1)]
-pub fn part1(input: &Input) -> u64 {
-    let mut result = 1;
-    let input: &mut Input = &mut Input {
-        map: input.map.clone(),
-        guard: input.guard,
-    };
+pub fn part1(input: &Input) -> Result<u64, SolveError> {
+    let mut visited = input.map.map(|_| false);
+    let mut result = 0;
+
+    for &guard in input.guards() {
+        let mut walk = Walk::new(input, guard);
+        for (pos, _) in walk.by_ref() {
+            if visited.set(pos, true) == Some(false) {
+                result += 1;
+            }
+        }
 
-    let mut delta = (0, -1);
+        leaves(&walk, guard)?;
+    }
 
-    let mut visited = HashSet::<Pos>::new();
-    visited.insert(input.guard);
+    Ok(result)
+}
 
-    loop {
-        let next_pos = Pos {
-            x: input.guard.x + delta.0,
-            y: input.guard.y + delta.1,
-        };
+/// Fail like part 1 when the finished `walk` of `guard` never left the map.
+fn leaves(walk: &Walk, guard: (Pos, Direction)) -> Result<(), SolveError> {
+    if walk.result() == Some(SimulationResult::Loop) {
+        return Err(SolveError::Unsolvable {
+            day: 6,
+            reason: format!(
+                "the guard from ({}, {}) never leaves the map",
+                guard.0.x, guard.0.y
+            ),
+        });
+    }
+    Ok(())
+}
 
-        if let Some(entity) = input.map.get(&next_pos) {
-            match entity {
-                Entity::Blank => {
-                    if !visited.contains(&next_pos) {
-                        visited.insert(next_pos);
-                        result += 1;
-                    }
+/// Fail like part 1 when a guard is in a loop before any obstacle is added.
+fn all_leave(input: &Input) -> Result<(), SolveError> {
+    for &guard in input.guards() {
+        let mut walk = Walk::new(input, guard);
+        walk.by_ref().for_each(drop);
+        leaves(&walk, guard)?;
+    }
+    Ok(())
+}
 
-                    input.guard = next_pos;
-                }
-                Entity::Obstacle => {
-                    delta = match delta {
-                        (0, -1) => (1, 0),
-                        (1, 0) => (0, 1),
-                        (0, 1) => (-1, 0),
-                        (-1, 0) => (0, -1),
-                        _ => panic!("Invalid delta"),
-                    };
-                }
-            }
-        } else {
-            break;
-        }
+/// Where an obstacle could go on the path of every guard, with the guard's
+/// state in front of it the first time it gets there. The walk up to that
+/// point is the same with or without the obstacle. Fails if a guard loops
+/// without one.
+fn candidates(input: &Input) -> Result<Vec<(Pos, Direction, Pos)>, SolveError> {
+    let mut candidates = vec![];
+
+    for &guard in input.guards() {
+        let mut tried = input.map.map(|_| false);
+        let mut walk = Walk::new(input, guard);
+        candidates.extend(
+            walk.by_ref()
+                .map(|(pos, direction)| (pos, direction, pos + direction))
+                .filter(|&(_, _, ahead)| {
+                    input.map.get(ahead) == Some(&Entity::Blank)
+                        && !input.is_start(ahead)
+                        && tried.set(ahead, true) == Some(false)
+                }),
+        );
+        leaves(&walk, guard)?;
     }
 
-    result
+    Ok(candidates)
+}
+
+/// Obstacles of `candidates` that trap their guard in a loop.
+fn loops(table: &JumpTable, candidates: &[(Pos, Direction, Pos)]) -> Vec<Pos> {
+    candidates
+        .iter()
+        .filter(|&&(pos, direction, obstacle)| {
+            table.simulate(pos, direction, Some(obstacle)) == SimulationResult::Loop
+        })
+        .map(|&(_, _, obstacle)| obstacle)
+        .collect()
+}
+
+/// Number of distinct positions, an obstacle counts once even if it traps
+/// several guards.
+fn count_distinct(mut positions: Vec<Pos>) -> u64 {
+    positions.sort_unstable_by_key(|pos| (pos.y, pos.x));
+    positions.dedup();
+    positions.len() as u64
 }
 
 #[aoc(day6, part2)]
-pub fn part2(input: &Input) -> u64 {
-    let input: &mut Input = &mut Input {
-        map: input.map.clone(),
-        guard: input.guard,
-    };
+pub fn part2(input: &Input) -> Result<u64, SolveError> {
+    Ok(count_distinct(loops(
+        &JumpTable::new(input),
+        &candidates(input)?,
+    )))
+}
 
-    let initial_guard = input.guard;
+#[aoc(day6, part2, parallel)]
+pub fn part2_parallel(input: &Input) -> Result<u64, SolveError> {
+    part2_threads(input, threads())
+}
 
-    // direction where guard is moving
-    let mut guard_direction = Direction::Up;
+/// Part 2 with the candidate obstacles split evenly between `threads`
+/// threads.
+pub fn part2_threads(input: &Input, threads: usize) -> Result<u64, SolveError> {
+    let table = JumpTable::new(input);
+    let candidates = candidates(input)?;
+    let chunk = candidates.len().div_ceil(threads.max(1)).max(1);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = candidates
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(|| loops(&table, chunk)))
+            .collect();
+
+        Ok(count_distinct(
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("worker thread panicked"))
+                .collect(),
+        ))
+    })
+}
 
-    let mut rocks_positions = HashSet::<Pos>::new();
+/// Threads chosen with [`set_threads`], 0 until then.
+static THREADS: AtomicUsize = AtomicUsize::new(0);
 
-    let mut visited = HashSet::<Pos>::new();
-    visited.insert(input.guard);
+/// Make [`part2_parallel`] use `threads` threads, or the default again if 0.
+pub fn set_threads(threads: usize) {
+    THREADS.store(threads, atomic::Ordering::Relaxed);
+}
 
-    // for each position we want to try to put a rock in front of the guard
-    // guard will turn to the right and if he will enter a loop we can increase
-    // number of rocks
-    loop {
-        let next_pos = input.guard + guard_direction;
+/// Threads used by [`part2_parallel`]: the count given to [`set_threads`],
+/// else `AOC_THREADS` when it is a positive number, otherwise one per core.
+pub fn threads() -> usize {
+    Some(THREADS.load(atomic::Ordering::Relaxed))
+        .filter(|&threads| threads > 0)
+        .or_else(|| {
+            env::var("AOC_THREADS")
+                .ok()
+                .and_then(|threads| threads.parse().ok())
+                .filter(|&threads| threads > 0)
+        })
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+}
 
-        if let Some(entity) = input.map.get(&next_pos) {
-            match entity {
-                Entity::Blank => {
-                    if visited.contains(&next_pos) {
-                        input.guard = next_pos;
-                        continue;
-                    }
+#[aoc(day6, part2, brute_force)]
+pub fn part2_bf(input: &Input) -> Result<u64, SolveError> {
+    all_leave(input)?;
+    let table = JumpTable::new(input);
+
+    let obstacles = input
+        .map
+        .iter()
+        .filter(|&(pos, entity)| *entity == Entity::Blank && !input.is_start(pos))
+        .filter(|&(pos, _)| {
+            input.guards().iter().any(|&(guard, direction)| {
+                table.simulate(guard, direction, Some(pos)) == SimulationResult::Loop
+            })
+        })
+        .count();
+
+    Ok(obstacles as u64)
+}
 
-                    // skip already visited positions
-                    // we shouldn't put a rock in position where guard has already been
-                    visited.insert(next_pos);
-
-                    let mut modified_input = input.clone();
-                    modified_input.map.insert(next_pos, Entity::Obstacle);
-
-                    // println!(
-                    //     "next pos {:?}, modified input map\n{}",
-                    //     next_pos,
-                    //     print_map(&modified_input, &HashMap::new())
-                    // );
-
-                    // let's see if we can put a rock in front of the guard
-                    // simulate until guard enter a loop or exit map
-                    match simulate(modified_input, guard_direction.clone()) {
-                        SimulationResult::Loop => {
-                            if next_pos != initial_guard {
-                                rocks_positions.insert(next_pos);
-                            }
-                        }
-                        SimulationResult::Exit => {}
-                    }
+/// How a traced walk ended.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Outcome {
+    /// The guard steps off the map from `pos`, facing `direction`.
+    Exit { pos: Pos, direction: Direction },
+    /// The guard comes back to `entry`, the state at index `start` of the
+    /// trace, and walks the same cycle forever.
+    Loop {
+        entry: (Pos, Direction),
+        start: usize,
+    },
+}
 
-                    input.guard = next_pos;
-                }
-                Entity::Obstacle => {
-                    guard_direction = match guard_direction {
-                        Direction::Up => Direction::Right,
-                        Direction::Right => Direction::Down,
-                        Direction::Down => Direction::Left,
-                        Direction::Left => Direction::Up,
-                    };
-                }
-            }
+/// Every state of a walk, in order, and how it ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub states: Vec<(Pos, Direction)>,
+    pub outcome: Outcome,
+}
 
-            // print_map(&input);
+impl Trace {
+    /// Moves and turns made, including the one off the map or back to the
+    /// start of the loop.
+    pub fn steps(&self) -> usize {
+        self.states.len()
+    }
 
-            // wait for keypress
-            // let mut _input = String::new();
-            // std::io::stdin().read_line(&mut _input).unwrap();
-        } else {
-            break;
+    pub fn result(&self) -> SimulationResult {
+        match self.outcome {
+            Outcome::Exit { .. } => SimulationResult::Exit,
+            Outcome::Loop { .. } => SimulationResult::Loop,
         }
     }
 
-    rocks_positions.len() as u64
+    /// States of the loop starting at its entry, empty if the guard exits.
+    pub fn cycle(&self) -> &[(Pos, Direction)] {
+        match self.outcome {
+            Outcome::Exit { .. } => &[],
+            Outcome::Loop { start, .. } => &self.states[start..],
+        }
+    }
 }
 
-#[aoc(day6, part2, brute_force)]
-pub fn part2_bf(input: &Input) -> u64 {
-    let input: &mut Input = &mut Input {
-        map: input.map.clone(),
-        guard: input.guard,
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.outcome {
+            Outcome::Exit { pos, direction } => write!(
+                f,
+                "exits at ({}, {}) facing {:?} after {} steps",
+                pos.x,
+                pos.y,
+                direction,
+                self.steps()
+            ),
+            Outcome::Loop {
+                entry: (pos, direction),
+                start,
+            } => write!(
+                f,
+                "enters a loop at ({}, {}) facing {:?} after {} steps, cycle of {} steps",
+                pos.x,
+                pos.y,
+                direction,
+                start,
+                self.cycle().len()
+            ),
+        }
+    }
+}
+
+/// Record the walk of `guard`, with an extra `obstacle` if any.
+pub fn trace(input: &Input, guard: (Pos, Direction), obstacle: Option<Pos>) -> Trace {
+    let mut walk = Walk::new(input, guard);
+    if let Some(obstacle) = obstacle {
+        walk = walk.with_obstacle(obstacle);
+    }
+
+    let states: Vec<(Pos, Direction)> = walk.by_ref().collect();
+    let outcome = match walk.repeated() {
+        Some(entry) => Outcome::Loop {
+            entry,
+            start: states
+                .iter()
+                .position(|&state| state == entry)
+                .expect("repeated state was walked"),
+        },
+        None => {
+            let (pos, direction) = *states.last().expect("walk has a first state");
+            Outcome::Exit { pos, direction }
+        }
     };
 
-    let initial_guard = input.guard;
-
-    let mut min_x = i64::MAX;
-    let mut max_x = i64::MIN;
-    let mut min_y = i64::MAX;
-    let mut max_y = i64::MIN;
-
-    input.map.iter().for_each(|(pos, _)| {
-        min_x = min_x.min(pos.x);
-        max_x = max_x.max(pos.x);
-        min_y = min_y.min(pos.y);
-        max_y = max_y.max(pos.y);
-    });
-
-    let mut rocks_positions = HashSet::<Pos>::new();
-
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            let pos = Pos { x, y };
-            if let Some(entity) = input.map.get(&pos) {
-                match entity {
-                    Entity::Blank => {
-                        let mut modified_input = input.clone();
-                        modified_input.map.insert(pos, Entity::Obstacle);
-                        match simulate(modified_input, Direction::Up) {
-                            SimulationResult::Loop => {
-                                if pos != initial_guard {
-                                    rocks_positions.insert(pos);
-                                }
-                            }
-                            SimulationResult::Exit => {}
-                        }
-                    }
-                    Entity::Obstacle => {}
-                }
-            }
+    Trace { states, outcome }
+}
+
+/// A guard's walk, one `(position, direction)` state per step, where a step
+/// is either a move forward, through a portal or not, or a turn to the
+/// right. Ends when the guard leaves the map or is about to repeat a state.
+pub struct Walk<'a> {
+    input: &'a Input,
+    obstacle: Option<Pos>,
+    next: Option<(Pos, Direction)>,
+    seen: HashSet<(Pos, Direction)>,
+    result: Option<SimulationResult>,
+    repeated: Option<(Pos, Direction)>,
+}
+
+impl<'a> Walk<'a> {
+    pub fn new(input: &'a Input, guard: (Pos, Direction)) -> Walk<'a> {
+        Walk {
+            input,
+            obstacle: None,
+            next: Some(guard),
+            seen: HashSet::new(),
+            result: None,
+            repeated: None,
         }
     }
 
-    rocks_positions.len() as u64
-}
+    /// Walk as if there was one more obstacle on the blank cell `obstacle`.
+    pub fn with_obstacle(mut self, obstacle: Pos) -> Walk<'a> {
+        self.obstacle = Some(obstacle);
+        self
+    }
 
-fn simulate(mut input: Input, mut guard_direction: Direction) -> SimulationResult {
-    let mut visited = HashSet::<(Pos, Direction)>::new();
-    // visited.insert(input.guard, vec![guard_direction]);
+    /// How the walk ended, `None` while it goes on.
+    pub fn result(&self) -> Option<SimulationResult> {
+        self.result
+    }
+
+    /// State the guard came back to, if the walk ended in a loop.
+    pub fn repeated(&self) -> Option<(Pos, Direction)> {
+        self.repeated
+    }
 
-    loop {
-        let next_pos = input.guard + guard_direction;
+    fn blocked(&self, pos: Pos, direction: Direction) -> bool {
+        let entity = self.input.map.get(pos);
+        self.obstacle == Some(pos) || entity.is_some_and(|e| e.blocks(direction))
+    }
+}
 
-        // println!("simulation step");
-        // print_map(&input);
+impl Iterator for Walk<'_> {
+    type Item = (Pos, Direction);
 
-        // std::thread::sleep(std::time::Duration::from_millis(10));
+    fn next(&mut self) -> Option<(Pos, Direction)> {
+        let state = self.next.take()?;
 
-        // if we have visited this position with same direction
-        // we are in a loop
-        if visited.contains(&(input.guard, guard_direction)) {
-            return SimulationResult::Loop;
+        // same position and direction as before, the guard is in a loop
+        if !self.seen.insert(state) {
+            self.result = Some(SimulationResult::Loop);
+            self.repeated = Some(state);
+            return None;
         }
 
-        // add this direction to visited
-        visited.insert((input.guard, guard_direction));
-
-        match input.map.get(&next_pos) {
-            Some(entity) => {
-                match entity {
-                    Entity::Blank => {
-                        input.guard = next_pos;
-                    }
-                    Entity::Obstacle => {
-                        // turn right
-                        guard_direction = match guard_direction {
-                            Direction::Up => Direction::Right,
-                            Direction::Right => Direction::Down,
-                            Direction::Down => Direction::Left,
-                            Direction::Left => Direction::Up,
-                        };
-                    }
-                }
-            }
-            None => return SimulationResult::Exit,
+        let (pos, direction) = state;
+        let ahead = pos + direction;
+        if !self.input.map.contains(ahead) {
+            self.result = Some(SimulationResult::Exit);
+        } else if self.blocked(ahead, direction) {
+            self.next = Some((pos, direction.turn_right()));
+        } else if self.input.map.get(ahead) == Some(&Entity::Portal) {
+            let exit = self
+                .input
+                .portal_exit(ahead)
+                .expect("portals have two ends");
+            self.next = Some((exit, direction));
+        } else {
+            self.next = Some((ahead, direction));
         }
+
+        Some(state)
     }
 }
 
-fn print_map<'a>(input: &'a Input, visited: &'a HashMap<Pos, Vec<Direction>>) -> String {
-    let mut min_x = i64::MAX;
-    let mut max_x = i64::MIN;
-    let mut min_y = i64::MAX;
-    let mut max_y = i64::MIN;
-
-    input.map.iter().for_each(|(pos, _)| {
-        min_x = min_x.min(pos.x);
-        max_x = max_x.max(pos.x);
-        min_y = min_y.min(pos.y);
-        max_y = max_y.max(pos.y);
-    });
-
-    let mut result = String::new();
-
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            let pos = Pos { x, y };
-            if pos == input.guard {
-                result.push_str("G");
-            } else if let Some(entity) = input.map.get(&pos) {
-                match entity {
-                    Entity::Blank => {
-                        if let Some(directions) = visited.get(&pos) {
-                            if directions.contains(&Direction::Up) {
-                                result.push_str("^");
-                            } else if directions.contains(&Direction::Right) {
-                                result.push_str(">");
-                            } else if directions.contains(&Direction::Down) {
-                                result.push_str("v");
-                            } else if directions.contains(&Direction::Left) {
-                                result.push_str("<");
-                            } else {
-                                result.push_str("?");
-                            }
-                        } else {
-                            result.push_str(".");
-                        }
-                        // line.push(Span::styled(".", Style::default().fg(Color::DarkGray)))
-                    }
-                    Entity::Obstacle => {
-                        result.push_str("#");
-                    }
-                }
-            } else {
-                result.push_str(" ");
-            }
-        }
-        result.push_str("\n");
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+
+    type Input = Input;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    result
+    fn part1(input: &Input) -> Result<Answer, SolveError> {
+        part1(input)
+    }
+
+    fn part2(input: &Input) -> Result<Answer, SolveError> {
+        part2(input)
+    }
+
+    fn variants() -> Vec<Variant<Input>> {
+        vec![
+            Variant {
+                part: Part::Two,
+                name: "brute_force",
+                run: part2_bf,
+            },
+            Variant {
+                part: Part::Two,
+                name: "parallel",
+                run: part2_parallel,
+            },
+        ]
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use crate::day6::*;
+    use crate::inputs::Inputs;
+    use crate::solution::{check_consistency, Part, DAYS};
 
-    static INPUT: &str = "....#.....
-.........#
-..........
-..#.......
-.......#..
-..........
-.#..^.....
-........#.
-#.........
-......#...";
+    fn example() -> String {
+        Inputs::fixtures().load(6, "example").unwrap()
+    }
 
     static LOOP: &str = ".#..
 .^.#
@@ -391,19 +542,192 @@ mod tests {
 
     #[test]
     fn test_day6_part1() {
-        assert_eq!(41, part1(&parse_input(INPUT)));
+        assert_eq!(Ok(41), part1(&parse_input(&example()).unwrap()));
     }
 
     #[test]
     fn test_day6_part2() {
-        assert_eq!(6, part2(&parse_input(INPUT)));
+        assert_eq!(Ok(6), part2(&parse_input(&example()).unwrap()));
+    }
+
+    #[test]
+    fn test_day6_part2_threads() {
+        let input = parse_input(&example()).unwrap();
+
+        for threads in [0, 1, 2, 5, 100] {
+            assert_eq!(Ok(6), part2_threads(&input, threads));
+        }
+
+        set_threads(3);
+        assert_eq!(3, threads());
+        assert_eq!(Ok(6), part2_parallel(&input));
+        set_threads(0);
+        assert!(threads() > 0);
+    }
+
+    #[test]
+    fn test_day6_invalid_entity() {
+        assert_eq!(
+            ParseError::UnexpectedToken {
+                day: 6,
+                line: 2,
+                column: 3,
+                token: "x".to_string(),
+                expected: "a grid cell",
+            },
+            parse_input("...\n.^x").err().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_day6_walk() {
+        let input = parse_input(LOOP).unwrap();
+        let mut walk = Walk::new(&input, input.guards()[0]);
+
+        assert_eq!(
+            vec![
+                (Pos::new(1, 1), Direction::Up),
+                (Pos::new(1, 1), Direction::Right),
+                (Pos::new(2, 1), Direction::Right),
+                (Pos::new(2, 1), Direction::Down),
+            ],
+            walk.by_ref().take(4).collect::<Vec<_>>()
+        );
+        assert_eq!(None, walk.result());
+        assert_eq!(4, walk.by_ref().count());
+        assert_eq!(Some(SimulationResult::Loop), walk.result());
+
+        let input = parse_input(&example()).unwrap();
+        let mut walk = Walk::new(&input, input.guards()[0]).with_obstacle(Pos::new(3, 6));
+        walk.by_ref().for_each(drop);
+        assert_eq!(Some(SimulationResult::Loop), walk.result());
+    }
+
+    #[test]
+    fn test_day6_trace() {
+        let input = parse_input(LOOP).unwrap();
+        let looping = trace(&input, input.guards()[0], None);
+        assert_eq!(SimulationResult::Loop, looping.result());
+        assert_eq!(8, looping.steps());
+        assert_eq!(
+            Outcome::Loop {
+                entry: (Pos::new(1, 1), Direction::Up),
+                start: 0
+            },
+            looping.outcome
+        );
+        assert_eq!(8, looping.cycle().len());
+
+        let input = parse_input(&example()).unwrap();
+        let exit = trace(&input, input.guards()[0], None);
+        assert_eq!(
+            Outcome::Exit {
+                pos: Pos::new(7, 9),
+                direction: Direction::Down
+            },
+            exit.outcome
+        );
+        assert_eq!(55, exit.steps());
+        assert!(exit.cycle().is_empty());
+        assert_eq!(
+            "exits at (7, 9) facing Down after 55 steps",
+            exit.to_string()
+        );
+
+        // the obstacle left of the guard sends it back up the same path
+        let looped = trace(&input, input.guards()[0], Some(Pos::new(3, 6)));
+        assert_eq!(SimulationResult::Loop, looped.result());
+        assert_eq!(looped.states[0], looped.cycle()[0]);
+        assert_eq!(looped.steps(), looped.cycle().len());
     }
 
     #[test]
     fn test_day6_loop() {
+        let input = parse_input(LOOP).unwrap();
         assert_eq!(
             SimulationResult::Loop,
-            simulate(parse_input(LOOP), Direction::Up,)
+            trace(&input, input.guards()[0], None).result()
+        );
+        assert_eq!(
+            Err(SolveError::Unsolvable {
+                day: 6,
+                reason: "the guard from (1, 1) never leaves the map".to_string()
+            }),
+            part1(&input)
+        );
+    }
+
+    #[test]
+    fn test_day6_loop_part2() {
+        let input = parse_input(LOOP).unwrap();
+        let unsolvable = part1(&input);
+        assert!(unsolvable.is_err());
+
+        assert_eq!(unsolvable, part2(&input));
+        assert_eq!(unsolvable, part2_bf(&input));
+        assert_eq!(unsolvable, part2_threads(&input, 2));
+    }
+
+    #[test]
+    fn test_day6_guards() {
+        // the example's guard facing right, then with a second guard facing down
+        // in the top left corner
+        let right = example().replace('^', ">");
+        assert_eq!(Ok(6), part1(&parse_input(&right).unwrap()));
+        assert_eq!(
+            Ok(14),
+            part1(&parse_input(&right.replacen("....", "v...", 1)).unwrap())
         );
     }
+
+    /// Map of `width` x `height` with obstacles where `obstacles` is true and
+    /// guards on the cells `guards`, with a direction index (both modulo
+    /// their range).
+    fn random_map(
+        width: usize,
+        height: usize,
+        obstacles: &[bool],
+        guards: &[(usize, usize)],
+    ) -> String {
+        let mut map = String::new();
+        for i in 0..width * height {
+            let guard = guards
+                .iter()
+                .find(|&&(guard, _)| guard % (width * height) == i);
+            map.push(match (guard, obstacles[i % obstacles.len()]) {
+                (Some(&(_, direction)), _) => Direction::ALL[direction % 4].arrow(),
+                (None, true) => '#',
+                (None, false) => '.',
+            });
+            if i % width == width - 1 {
+                map.push('\n');
+            }
+        }
+        map
+    }
+
+    proptest! {
+        #[test]
+        fn test_day6_variants_agree(
+            width in 1usize..12,
+            height in 1usize..12,
+            obstacles in prop::collection::vec(prop::bool::weighted(0.15), 1..144),
+            guards in prop::collection::vec((0usize..144, 0usize..4), 1..3),
+        ) {
+            let input = random_map(width, height, &obstacles, &guards);
+            let part1 = part1(&parse_input(&input).unwrap());
+
+            for part in Part::ALL {
+                let consistency = check_consistency(DAYS[5], &input, part).unwrap();
+                if part1.is_ok() {
+                    prop_assert!(consistency.is_consistent(), "{}\n{:?}", input, consistency);
+                } else {
+                    // a guard already loops, every variant fails the same way
+                    for (_, answer) in &consistency.answers {
+                        prop_assert_eq!(&part1, answer, "{}", input);
+                    }
+                }
+            }
+        }
+    }
 }
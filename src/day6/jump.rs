@@ -0,0 +1,342 @@
+//! Simulation that jumps from obstacle to obstacle instead of walking cell
+//! by cell.
+
+use std::collections::HashMap;
+
+use crate::day6::{Direction, Entity, Input, Pos, SimulationResult};
+
+/// Marks a guard that leaves the map instead of hitting an obstacle.
+const EXIT: u32 = u32::MAX;
+
+/// Marks a stop on a portal rather than in front of an obstacle.
+const PORTAL: u32 = 1 << 31;
+
+/// Where a guard walking straight ahead stops.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// On this cell, in front of an obstacle.
+    Turn(Pos),
+    /// On the `entrance` of a portal, coming out on `exit`.
+    Portal {
+        entrance: Pos,
+        exit: Pos,
+    },
+    Exit,
+}
+
+/// For every cell and direction, where a guard walking that way stops. Built
+/// backwards from the edge of the map along every row and column, so it
+/// takes linear time.
+#[derive(Debug, Clone)]
+pub struct JumpTable {
+    width: usize,
+    height: usize,
+    /// Index of the stop cell per cell, by [`Direction::index`].
+    stops: Vec<[u32; 4]>,
+    /// Other end of the portal on each portal cell.
+    portals: HashMap<u32, Pos>,
+}
+
+impl JumpTable {
+    pub fn new(input: &Input) -> JumpTable {
+        let map = &input.map;
+        let (width, height) = (map.width(), map.height());
+        let index = |pos: Pos| (pos.y as usize * width + pos.x as usize) as u32;
+
+        let mut stops = vec![[EXIT; 4]; width * height];
+        let mut portals = HashMap::new();
+
+        for (&pos, &exit) in &input.portals {
+            portals.insert(index(pos), exit);
+        }
+
+        for direction in Direction::ALL {
+            let delta = direction.delta();
+            let behind = |pos: Pos| Pos::new(pos.x - delta.x, pos.y - delta.y);
+            let d = direction.index();
+
+            // every line starts on the edge the guard walks off, the stop of
+            // each cell depends on the cell ahead of it
+            let (w, h) = (width as i64, height as i64);
+            let edges: Vec<Pos> = match direction {
+                Direction::Up => (0..w).map(|x| Pos::new(x, 0)).collect(),
+                Direction::Right => (0..h).map(|y| Pos::new(w - 1, y)).collect(),
+                Direction::Down => (0..w).map(|x| Pos::new(x, h - 1)).collect(),
+                Direction::Left => (0..h).map(|y| Pos::new(0, y)).collect(),
+            };
+            for edge in edges {
+                let (mut ahead, mut pos) = (edge, behind(edge));
+
+                while map.contains(pos) {
+                    stops[index(pos) as usize][d] = match map.get(ahead) {
+                        Some(entity) if entity.blocks(direction) => index(pos),
+                        Some(Entity::Portal) => index(ahead) | PORTAL,
+                        _ => stops[index(ahead) as usize][d],
+                    };
+
+                    (ahead, pos) = (pos, behind(pos));
+                }
+            }
+        }
+
+        JumpTable {
+            width,
+            height,
+            stops,
+            portals,
+        }
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        pos.y as usize * self.width + pos.x as usize
+    }
+
+    fn pos(&self, index: u32) -> Pos {
+        Pos::new(
+            (index as usize % self.width) as i64,
+            (index as usize / self.width) as i64,
+        )
+    }
+
+    /// Where the guard on `pos` facing `direction` stops. `obstacle` is an
+    /// extra obstacle on top of the map.
+    pub fn jump(&self, pos: Pos, direction: Direction, obstacle: Option<Pos>) -> Stop {
+        let stop = match self.stops[self.index(pos)][direction.index()] {
+            EXIT => Stop::Exit,
+            stop if stop & PORTAL != 0 => Stop::Portal {
+                entrance: self.pos(stop & !PORTAL),
+                exit: self.portals[&(stop & !PORTAL)],
+            },
+            stop => Stop::Turn(self.pos(stop)),
+        };
+
+        let Some(obstacle) = obstacle else {
+            return stop;
+        };
+
+        // distance along `direction` to the extra obstacle, when it is ahead
+        // on the same row or column
+        let delta = direction.delta();
+        let along = |to: Pos| (to.x - pos.x) * delta.x + (to.y - pos.y) * delta.y;
+        if (obstacle.x - pos.x) * delta.y != (obstacle.y - pos.y) * delta.x {
+            return stop;
+        }
+        let distance = along(obstacle);
+        let reach = match stop {
+            Stop::Turn(stop) => along(stop),
+            Stop::Portal { entrance, .. } => along(entrance),
+            Stop::Exit => i64::MAX,
+        };
+
+        if distance > 0 && distance <= reach {
+            Stop::Turn(Pos::new(obstacle.x - delta.x, obstacle.y - delta.y))
+        } else {
+            stop
+        }
+    }
+
+    /// Whether the guard on `guard` facing `direction` leaves the map or
+    /// walks in a loop, with an extra `obstacle` if any. Only the cells where
+    /// the guard turns or comes out of a portal are remembered, a loop always
+    /// repeats one of them.
+    pub fn simulate(
+        &self,
+        guard: Pos,
+        direction: Direction,
+        obstacle: Option<Pos>,
+    ) -> SimulationResult {
+        // per cell, one bit per direction the guard turned in and one per
+        // direction it came out of a portal in
+        let mut seen = vec![0u8; self.width * self.height];
+        let (mut pos, mut direction) = (guard, direction);
+
+        loop {
+            let (cell, bit) = match self.jump(pos, direction, obstacle) {
+                Stop::Exit => return SimulationResult::Exit,
+                Stop::Turn(stop) => {
+                    let bit = 1 << direction.index();
+                    direction = direction.turn_right();
+                    (stop, bit)
+                }
+                Stop::Portal { exit, .. } => (exit, 0x10 << direction.index()),
+            };
+
+            let seen = &mut seen[self.index(cell)];
+            if *seen & bit != 0 {
+                return SimulationResult::Loop;
+            }
+            *seen |= bit;
+            pos = cell;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::day6::jump::*;
+    use crate::day6::{parse_input, Legend, Tile, Walk};
+    use crate::inputs::Inputs;
+
+    fn example() -> String {
+        Inputs::fixtures().load(6, "example").unwrap()
+    }
+
+    #[test]
+    fn test_jump() {
+        let input = parse_input(&example()).unwrap();
+        let table = JumpTable::new(&input);
+        let guard = input.guards()[0].0;
+
+        assert_eq!(
+            Stop::Turn(Pos::new(4, 1)),
+            table.jump(guard, Direction::Up, None)
+        );
+        assert_eq!(
+            Stop::Turn(Pos::new(8, 1)),
+            table.jump(Pos::new(4, 1), Direction::Right, None)
+        );
+        assert_eq!(
+            Stop::Exit,
+            table.jump(Pos::new(5, 0), Direction::Down, None)
+        );
+        assert_eq!(
+            Stop::Exit,
+            table.jump(Pos::new(0, 0), Direction::Left, None)
+        );
+
+        // extra obstacle in the way, behind the guard, past the real stop
+        let up = |obstacle| table.jump(guard, Direction::Up, Some(obstacle));
+        assert_eq!(Stop::Turn(Pos::new(4, 4)), up(Pos::new(4, 3)));
+        assert_eq!(Stop::Turn(Pos::new(4, 1)), up(Pos::new(4, 8)));
+        assert_eq!(Stop::Turn(Pos::new(4, 1)), up(Pos::new(5, 3)));
+        assert_eq!(
+            Stop::Turn(Pos::new(5, 7)),
+            table.jump(Pos::new(5, 0), Direction::Down, Some(Pos::new(5, 8)))
+        );
+
+        assert_eq!(
+            SimulationResult::Exit,
+            table.simulate(guard, Direction::Up, None)
+        );
+        assert_eq!(
+            SimulationResult::Loop,
+            table.simulate(guard, Direction::Up, Some(Pos::new(3, 6)))
+        );
+    }
+
+    #[test]
+    fn test_custom_tiles() {
+        let legend = Legend::default()
+            .with('*', Tile::Portal)
+            .with('}', Tile::OneWay(Direction::Right));
+        let input = legend.parse("..*.\n>}.*\n....").unwrap();
+        let table = JumpTable::new(&input);
+
+        assert_eq!(
+            Stop::Portal {
+                entrance: Pos::new(3, 1),
+                exit: Pos::new(2, 0)
+            },
+            table.jump(Pos::new(0, 1), Direction::Right, None)
+        );
+        // the one-way tile only lets guards walking right through
+        assert_eq!(
+            Stop::Turn(Pos::new(2, 1)),
+            table.jump(Pos::new(3, 1), Direction::Left, None)
+        );
+        assert_eq!(
+            Stop::Turn(Pos::new(1, 0)),
+            table.jump(Pos::new(1, 0), Direction::Down, None)
+        );
+        assert_eq!(
+            Stop::Turn(Pos::new(2, 1)),
+            table.jump(Pos::new(0, 1), Direction::Right, Some(Pos::new(3, 1)))
+        );
+
+        // through the portal, then right off the map
+        assert_eq!(
+            SimulationResult::Exit,
+            table.simulate(Pos::new(0, 1), Direction::Right, None)
+        );
+
+        // around and around through the portal, without ever turning
+        let input = legend.parse("*.>*\n....").unwrap();
+        let table = JumpTable::new(&input);
+        assert_eq!(
+            SimulationResult::Loop,
+            table.simulate(Pos::new(2, 0), Direction::Right, None)
+        );
+    }
+
+    /// Map of `width` x `height` with random `tiles`: obstacles, one-way
+    /// tiles `}` and `{`, a portal `*` and the guard facing `direction`.
+    fn random_tiles(
+        width: usize,
+        height: usize,
+        tiles: &[u8],
+        guard: usize,
+        direction: usize,
+    ) -> String {
+        let guard = guard % (width * height);
+        let mut map = String::new();
+        for i in 0..width * height {
+            map.push(match (i == guard, tiles[i % tiles.len()]) {
+                (true, _) => ['^', '>', 'v', '<'][direction],
+                (false, 0..=2) => '#',
+                (false, 3) => '}',
+                (false, 4) => '{',
+                _ => '.',
+            });
+            if i % width == width - 1 {
+                map.push('\n');
+            }
+        }
+        map
+    }
+
+    proptest! {
+        #[test]
+        fn test_same_as_walk(
+            width in 1usize..12,
+            height in 1usize..12,
+            tiles in prop::collection::vec(0u8..20, 1..144),
+            guard in 0usize..144,
+            direction in 0usize..4,
+            portal in prop::option::of((0usize..144, 0usize..144)),
+            extra in 0usize..144,
+        ) {
+            let mut map: Vec<char> = random_tiles(width, height, &tiles, guard, direction).chars().collect();
+            let cell = |i: usize| i % (width * height) / width * (width + 1) + i % width;
+            if let Some((a, b)) = portal {
+                prop_assume!(cell(a) != cell(b) && map[cell(a)] == '.' && map[cell(b)] == '.');
+                map[cell(a)] = '*';
+                map[cell(b)] = '*';
+            }
+            prop_assume!(map[cell(extra)] == '.');
+
+            let legend = Legend::default()
+                .with('*', Tile::Portal)
+                .with('}', Tile::OneWay(Direction::Right))
+                .with('{', Tile::OneWay(Direction::Up));
+            let input = legend.parse(&map.into_iter().collect::<String>()).unwrap();
+            let table = JumpTable::new(&input);
+            let (guard, direction) = input.guards()[0];
+            let extra = Pos::new((extra % width) as i64, (extra % (width * height) / width) as i64);
+
+            for obstacle in [None, Some(extra)] {
+                let mut walk = Walk::new(&input, (guard, direction));
+                if let Some(obstacle) = obstacle {
+                    walk = walk.with_obstacle(obstacle);
+                }
+                walk.by_ref().for_each(drop);
+
+                prop_assert_eq!(
+                    walk.result().unwrap(),
+                    table.simulate(guard, direction, obstacle)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Characters a map is drawn with, so maps with more kinds of tiles than the
+//! puzzle's can be parsed.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::day6::{Direction, Entity, Input, Pos};
+use crate::error::ParseError;
+use crate::grid::Grid;
+
+/// What a character on the map stands for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Tile {
+    Blank,
+    Obstacle,
+    /// Blank cell with a guard starting on it, facing the direction.
+    Guard(Direction),
+    /// Cell that can only be walked onto facing the direction.
+    OneWay(Direction),
+    /// One end of a portal, linked to the only other cell with the same
+    /// character.
+    Portal,
+}
+
+/// Mapping from map characters to tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Legend {
+    tiles: HashMap<char, Tile>,
+}
+
+impl Default for Legend {
+    /// The puzzle's legend: `.` blank, `#` obstacle and a guard drawn as
+    /// `^`, `>`, `v` or `<`.
+    fn default() -> Legend {
+        Legend::empty()
+            .with('.', Tile::Blank)
+            .with('#', Tile::Obstacle)
+            .with('^', Tile::Guard(Direction::Up))
+            .with('>', Tile::Guard(Direction::Right))
+            .with('v', Tile::Guard(Direction::Down))
+            .with('<', Tile::Guard(Direction::Left))
+    }
+}
+
+impl Legend {
+    /// Legend without any character.
+    pub fn empty() -> Legend {
+        Legend {
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Draw `tile` as `c`, replacing what `c` stood for before.
+    pub fn with(mut self, c: char, tile: Tile) -> Legend {
+        self.tiles.insert(c, tile);
+        self
+    }
+
+    pub fn get(&self, c: char) -> Option<Tile> {
+        self.tiles.get(&c).copied()
+    }
+
+    /// Parse a map drawn with this legend. Every map needs at least one
+    /// guard, and every portal character exactly two cells.
+    pub fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        // the map is mostly ASCII, skip hashing for it
+        let ascii: Vec<Option<Tile>> = (0..128u8).map(|c| self.get(c as char)).collect();
+        let lookup = |c: char| match ascii.get(c as usize) {
+            Some(&tile) => tile,
+            None => self.get(c),
+        };
+
+        let chars = Grid::try_parse(6, input, |c| lookup(c).map(|_| c))?;
+        let tile = |c: char| lookup(c).expect("parsed characters are in the legend");
+
+        let mut guards = vec![];
+        let mut ends: BTreeMap<char, Vec<Pos>> = BTreeMap::new();
+        for (pos, &c) in chars.iter() {
+            match tile(c) {
+                Tile::Guard(direction) => guards.push((pos, direction)),
+                Tile::Portal => ends.entry(c).or_default().push(pos),
+                _ => {}
+            }
+        }
+
+        if guards.is_empty() {
+            return Err(ParseError::UnexpectedEnd {
+                day: 6,
+                line: chars.height() + 1,
+                column: 1,
+                expected: "a guard `^`, `>`, `v` or `<` on the map",
+            });
+        }
+
+        let map = chars.map(|&c| match tile(c) {
+            Tile::Blank | Tile::Guard(_) => Entity::Blank,
+            Tile::Obstacle => Entity::Obstacle,
+            Tile::OneWay(direction) => Entity::OneWay(direction),
+            Tile::Portal => Entity::Portal,
+        });
+
+        let mut portals = HashMap::new();
+        for (c, ends) in ends {
+            let &[a, b] = ends.as_slice() else {
+                let pos = ends.get(2).unwrap_or(&ends[0]);
+                return Err(ParseError::UnexpectedToken {
+                    day: 6,
+                    line: pos.y as usize + 1,
+                    column: pos.x as usize + 1,
+                    token: c.to_string(),
+                    expected: "exactly two cells of each portal",
+                });
+            };
+            portals.insert(a, b);
+            portals.insert(b, a);
+        }
+
+        Ok(Input {
+            map,
+            guards,
+            portals,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day6::legend::*;
+
+    #[test]
+    fn test_guards() {
+        let input = Legend::default().parse(">..\n.#v\n...").unwrap();
+
+        assert_eq!(
+            vec![
+                (Pos::new(0, 0), Direction::Right),
+                (Pos::new(2, 1), Direction::Down)
+            ],
+            input.guards()
+        );
+        assert_eq!(Some(&Entity::Blank), input.map.get(Pos::new(2, 1)));
+    }
+
+    #[test]
+    fn test_custom_tiles() {
+        let legend = Legend::default()
+            .with('*', Tile::Portal)
+            .with('+', Tile::Portal)
+            .with('}', Tile::OneWay(Direction::Right));
+        let input = legend.parse("*.+\n.}.\n^+*").unwrap();
+
+        assert_eq!(Some(&Entity::Portal), input.map.get(Pos::new(0, 0)));
+        assert_eq!(Some(Pos::new(2, 2)), input.portal_exit(Pos::new(0, 0)));
+        assert_eq!(Some(Pos::new(1, 2)), input.portal_exit(Pos::new(2, 0)));
+        assert_eq!(None, input.portal_exit(Pos::new(1, 0)));
+        assert_eq!(
+            Some(&Entity::OneWay(Direction::Right)),
+            input.map.get(Pos::new(1, 1))
+        );
+
+        assert_eq!(
+            ParseError::UnexpectedToken {
+                day: 6,
+                line: 2,
+                column: 3,
+                token: "*".to_string(),
+                expected: "exactly two cells of each portal",
+            },
+            legend.parse("*.*\n^.*").unwrap_err()
+        );
+        assert_eq!(
+            ParseError::UnexpectedToken {
+                day: 6,
+                line: 1,
+                column: 2,
+                token: "+".to_string(),
+                expected: "exactly two cells of each portal",
+            },
+            legend.parse(".+.\n^..").unwrap_err()
+        );
+    }
+}
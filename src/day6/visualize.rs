@@ -0,0 +1,344 @@
+//! Terminal playback of the guard's walk, frame by frame, driven by
+//! [`Walk`] so it shows exactly what the solutions simulate.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, Write},
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute, queue,
+    style::{Color, Stylize},
+    terminal,
+};
+
+use crate::day6::{Direction, Entity, Input, Pos, SimulationResult, Walk};
+
+const MIN_DELAY: Duration = Duration::from_millis(1);
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Part of the map shown in a frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct View {
+    pub origin: Pos,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl View {
+    /// Window of at most `width` x `height` cells centered on `center`,
+    /// without going past the edges of a `map_width` x `map_height` map.
+    pub fn around(
+        center: Pos,
+        (map_width, map_height): (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> View {
+        let clamp = |center: i64, len: usize, map_len: usize| {
+            (center - len as i64 / 2).clamp(0, map_len.saturating_sub(len) as i64)
+        };
+
+        View {
+            origin: Pos::new(
+                clamp(center.x, width, map_width),
+                clamp(center.y, height, map_height),
+            ),
+            width: width.min(map_width),
+            height: height.min(map_height),
+        }
+    }
+}
+
+/// Replay of the guard's walk, with the directions it crossed every cell in.
+pub struct Player<'a> {
+    input: &'a Input,
+    obstacle: Option<Pos>,
+    walk: Walk<'a>,
+    guard: Option<(Pos, Direction)>,
+    visited: HashMap<Pos, Vec<Direction>>,
+    steps: usize,
+    delay: Duration,
+    paused: bool,
+}
+
+impl<'a> Player<'a> {
+    /// Player of the walk of `guard`, with an extra candidate `obstacle` if
+    /// any, advancing every `delay`.
+    pub fn new(
+        input: &'a Input,
+        guard: (Pos, Direction),
+        obstacle: Option<Pos>,
+        delay: Duration,
+    ) -> Player<'a> {
+        let mut walk = Walk::new(input, guard);
+        if let Some(obstacle) = obstacle {
+            walk = walk.with_obstacle(obstacle);
+        }
+
+        let mut player = Player {
+            input,
+            obstacle,
+            walk,
+            guard: None,
+            visited: HashMap::new(),
+            steps: 0,
+            delay: delay.clamp(MIN_DELAY, MAX_DELAY),
+            paused: false,
+        };
+        player.advance();
+        player.steps = 0;
+
+        player
+    }
+
+    /// Move to the next state of the walk, false once it has ended.
+    pub fn advance(&mut self) -> bool {
+        match self.walk.next() {
+            Some((pos, direction)) => {
+                let directions = self.visited.entry(pos).or_default();
+                if !directions.contains(&direction) {
+                    directions.push(direction);
+                }
+                self.guard = Some((pos, direction));
+                self.steps += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Advance until the walk ends.
+    pub fn finish(&mut self) -> SimulationResult {
+        while self.advance() {}
+        self.result().expect("walk has ended")
+    }
+
+    pub fn result(&self) -> Option<SimulationResult> {
+        self.walk.result()
+    }
+
+    /// Number of cells the guard has been on so far.
+    pub fn visited(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// Draw the cells of `view`: the guard as an arrow, visited cells as `|`,
+    /// `-` or `+` depending on the directions they were crossed in, the
+    /// candidate obstacle as `O`, one-way cells as `↑`, `→`, `↓` or `←` and
+    /// portals as `@`. With `color` visited cells are also shaded and the
+    /// candidate obstacle is red.
+    pub fn frame(&self, view: View, color: bool) -> String {
+        let mut frame = String::new();
+
+        for y in view.origin.y..view.origin.y + view.height as i64 {
+            for x in view.origin.x..view.origin.x + view.width as i64 {
+                let pos = Pos::new(x, y);
+                let shade = self.visited.contains_key(&pos).then_some(Color::DarkGrey);
+                let (c, fg, bg) = match (self.guard, self.input.map.get(pos)) {
+                    (Some((guard, direction)), _) if guard == pos => {
+                        (direction.arrow(), Color::Yellow, Some(Color::DarkGrey))
+                    }
+                    _ if self.obstacle == Some(pos) => ('O', Color::Red, None),
+                    (_, Some(Entity::Obstacle)) => ('#', Color::White, None),
+                    (_, Some(Entity::OneWay(way))) => (one_way(*way), Color::White, shade),
+                    (_, Some(Entity::Portal)) => ('@', Color::Magenta, shade),
+                    (_, Some(Entity::Blank)) => match self.visited.get(&pos) {
+                        Some(directions) => (trail(directions), Color::Cyan, Some(Color::DarkGrey)),
+                        None => ('.', Color::DarkGrey, None),
+                    },
+                    (_, None) => (' ', Color::Reset, None),
+                };
+
+                match (color, bg) {
+                    (false, _) => frame.push(c),
+                    (true, Some(bg)) => write!(frame, "{}", c.with(fg).on(bg)).unwrap(),
+                    (true, None) => write!(frame, "{}", c.with(fg)).unwrap(),
+                }
+            }
+            frame.push('\n');
+        }
+
+        frame
+    }
+
+    /// One line summary of the walk so far.
+    pub fn status(&self) -> String {
+        let (pos, direction) = self.guard.expect("walk has a first state");
+        let state = match self.result() {
+            Some(SimulationResult::Exit) => " exits the map".to_string(),
+            Some(SimulationResult::Loop) => " is in a loop".to_string(),
+            None if self.paused => " paused".to_string(),
+            None => format!(" every {:?}", self.delay),
+        };
+
+        format!(
+            "step {} at ({}, {}) facing {:?}, {} cells visited,{}",
+            self.steps,
+            pos.x,
+            pos.y,
+            direction,
+            self.visited(),
+            state
+        )
+    }
+
+    /// Play the walk in the terminal until it is quit with `q`. Space pauses,
+    /// `n` or the right arrow steps once, `+` and `-` change the speed.
+    pub fn play(&mut self, out: &mut impl Write) -> io::Result<Option<SimulationResult>> {
+        terminal::enable_raw_mode()?;
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+        let result = self.event_loop(out);
+
+        execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+
+        result.map(|()| self.result())
+    }
+
+    fn event_loop(&mut self, out: &mut impl Write) -> io::Result<()> {
+        loop {
+            self.draw(out)?;
+
+            let waiting = self.paused || self.result().is_some();
+            if !waiting && !event::poll(self.delay)? {
+                self.advance();
+                continue;
+            }
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('n') | KeyCode::Right => {
+                    self.paused = true;
+                    self.advance();
+                }
+                KeyCode::Char('+') => self.delay = (self.delay / 2).max(MIN_DELAY),
+                KeyCode::Char('-') => self.delay = (self.delay * 2).min(MAX_DELAY),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let (guard, _) = self.guard.expect("walk has a first state");
+        let size = (self.input.map.width(), self.input.map.height());
+        let view = View::around(
+            guard,
+            size,
+            columns as usize,
+            rows.saturating_sub(2) as usize,
+        );
+
+        queue!(
+            out,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        for line in self.frame(view, true).lines() {
+            write!(out, "{}\r\n", line)?;
+        }
+        write!(
+            out,
+            "{}\r\nspace: pause, n: step, +/-: speed, q: quit",
+            self.status()
+        )?;
+
+        out.flush()
+    }
+}
+
+fn one_way(direction: Direction) -> char {
+    match direction {
+        Direction::Up => '↑',
+        Direction::Right => '→',
+        Direction::Down => '↓',
+        Direction::Left => '←',
+    }
+}
+
+/// Path through a cell crossed in `directions`, like on the puzzle page.
+fn trail(directions: &[Direction]) -> char {
+    let vertical = |d: &Direction| matches!(d, Direction::Up | Direction::Down);
+
+    match (
+        directions.iter().any(vertical),
+        directions.iter().any(|d| !vertical(d)),
+    ) {
+        (true, true) => '+',
+        (true, false) => '|',
+        _ => '-',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day6::visualize::*;
+    use crate::day6::{parse_input, part1};
+    use crate::inputs::Inputs;
+
+    fn example() -> String {
+        Inputs::fixtures().load(6, "example").unwrap()
+    }
+
+    #[test]
+    fn test_frame() {
+        let input = parse_input(&example()).unwrap();
+        let guard = input.guards()[0];
+        let mut player = Player::new(&input, guard, Some(Pos::new(3, 6)), Duration::ZERO);
+        let view = View::around(Pos::new(4, 6), (10, 10), 10, 10);
+
+        for _ in 0..10 {
+            player.advance();
+        }
+        assert_eq!(
+            "....#.....
+....+--->#
+....|.....
+..#.|.....
+....|..#..
+....|.....
+.#.O|.....
+........#.
+#.........
+......#...
+",
+            player.frame(view, false)
+        );
+
+        assert_eq!(SimulationResult::Loop, player.finish());
+    }
+
+    #[test]
+    fn test_finish() {
+        let input = parse_input(&example()).unwrap();
+        let mut player = Player::new(&input, input.guards()[0], None, Duration::ZERO);
+
+        assert_eq!(SimulationResult::Exit, player.finish());
+        assert_eq!(part1(&input), Ok(player.visited() as u64));
+    }
+
+    #[test]
+    fn test_view() {
+        let view = View::around(Pos::new(1, 50), (100, 100), 20, 10);
+
+        assert_eq!(Pos::new(0, 45), view.origin);
+        assert_eq!((20, 10), (view.width, view.height));
+        assert_eq!((5, 5), {
+            let view = View::around(Pos::new(1, 1), (5, 5), 80, 24);
+            (view.width, view.height)
+        });
+    }
+}
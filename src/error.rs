@@ -0,0 +1,179 @@
+use std::{error::Error, fmt, str::FromStr};
+
+/// Malformed puzzle input. Lines and columns are 1-based so they match what
+/// an editor shows for the input file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Token that should be a number but isn't one.
+    InvalidNumber {
+        day: u8,
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// Token that is not allowed at this position.
+    UnexpectedToken {
+        day: u8,
+        line: usize,
+        column: usize,
+        token: String,
+        expected: &'static str,
+    },
+    /// Line or input ended before an expected token.
+    UnexpectedEnd {
+        day: u8,
+        line: usize,
+        column: usize,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidNumber {
+                day,
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "day {}, line {}, column {}: `{}` is not a valid number",
+                day, line, column, token
+            ),
+            ParseError::UnexpectedToken {
+                day,
+                line,
+                column,
+                token,
+                expected,
+            } => write!(
+                f,
+                "day {}, line {}, column {}: unexpected `{}`, expected {}",
+                day, line, column, token, expected
+            ),
+            ParseError::UnexpectedEnd {
+                day,
+                line,
+                column,
+                expected,
+            } => write!(
+                f,
+                "day {}, line {}, column {}: unexpected end of input, expected {}",
+                day, line, column, expected
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Failure running a solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    UnknownDay(u8),
+    /// Variant missing from `part`, or from every part when `None`.
+    UnknownVariant {
+        day: u8,
+        part: Option<u8>,
+        name: String,
+    },
+    /// Input parsed fine but has no answer, e.g. contradicting rules.
+    Unsolvable {
+        day: u8,
+        reason: String,
+    },
+    /// Answer doesn't fit in an [`Answer`](crate::solution::Answer).
+    Overflow {
+        day: u8,
+        part: u8,
+    },
+    /// Answer is below zero, which an [`Answer`](crate::solution::Answer)
+    /// can't hold.
+    Negative {
+        day: u8,
+        part: u8,
+        answer: i128,
+    },
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(err) => err.fmt(f),
+            SolveError::UnknownDay(day) => write!(f, "day {} is not solved", day),
+            SolveError::UnknownVariant {
+                day,
+                part: Some(part),
+                name,
+            } => write!(f, "day {} part {} has no variant `{}`", day, part, name),
+            SolveError::UnknownVariant {
+                day,
+                part: None,
+                name,
+            } => write!(f, "day {} has no variant `{}`", day, name),
+            SolveError::Unsolvable { day, reason } => {
+                write!(f, "day {} has no answer: {}", day, reason)
+            }
+            SolveError::Overflow { day, part } => {
+                write!(
+                    f,
+                    "day {} part {}: answer doesn't fit in 64 bits",
+                    day, part
+                )
+            }
+            SolveError::Negative { day, part, answer } => {
+                write!(
+                    f,
+                    "day {} part {}: answer {} is negative",
+                    day, part, answer
+                )
+            }
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SolveError::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(err: ParseError) -> SolveError {
+        SolveError::Parse(err)
+    }
+}
+
+/// Parse `token` found at `line`/`column` as a number.
+pub(crate) fn number<T: FromStr>(
+    day: u8,
+    line: usize,
+    column: usize,
+    token: &str,
+) -> Result<T, ParseError> {
+    token.parse().map_err(|_| ParseError::InvalidNumber {
+        day,
+        line,
+        column,
+        token: token.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::*;
+
+    #[test]
+    fn test_display() {
+        let err = number::<u64>(5, 3, 4, "x1").unwrap_err();
+        assert_eq!(
+            "day 5, line 3, column 4: `x1` is not a valid number",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,393 @@
+//! Deterministic generators of large valid puzzle inputs, for benchmarks and
+//! fuzzing. The same seed always produces the same input.
+
+use std::fmt::Write;
+
+use crate::day6;
+
+/// SplitMix64, small and fast, and unlike an external crate its sequence
+/// never changes between versions.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform number in `low..high`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "empty range {}..{}", low, high);
+        low + self.next_u64() % (high - low)
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(0, i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Input of roughly `bytes` bytes for `day`, or `None` for days without a
+/// generator.
+pub fn generate(day: u8, seed: u64, bytes: usize) -> Option<String> {
+    let side = ((bytes as f64).sqrt() as usize).max(2);
+    // real reports have up to 8 levels, bigger inputs get longer ones
+    let max_levels = (side / 4).max(8);
+
+    Some(match day {
+        1 => day1(seed, bytes / 14 + 1),
+        // about 3 bytes per level
+        2 => day2(seed, bytes * 2 / (3 * (5 + max_levels)) + 1, max_levels),
+        3 => day3(seed, bytes),
+        4 => day4(seed, side, side),
+        5 => {
+            let pages = ((bytes as f64 / 6.0).sqrt() as usize).max(5);
+            day5(seed, pages, bytes / 80 + 1)
+        }
+        6 => day6(seed, side, side, 0.04),
+        _ => return None,
+    })
+}
+
+/// Two columns of 5 digit location ids, `lines` lines.
+pub fn day1(seed: u64, lines: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut input = String::with_capacity(lines * 14);
+
+    for _ in 0..lines {
+        let left = rng.range(10_000, 100_000);
+        let right = rng.range(10_000, 100_000);
+        writeln!(input, "{}   {}", left, right).unwrap();
+    }
+
+    input
+}
+
+/// `reports` reports of 5 up to `max_levels` levels. Most are safe or off by
+/// a single bad level, like in the real input.
+pub fn day2(seed: u64, reports: usize, max_levels: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut input = String::new();
+
+    for _ in 0..reports {
+        let len = rng.range(5, max_levels.max(5) as u64 + 1) as usize;
+        let sign = if rng.chance(0.5) { 1 } else { -1 };
+        let mut level = rng.range(10, 90) as i64;
+
+        let mut levels = Vec::with_capacity(len);
+        for i in 0..len {
+            levels.push(level);
+            let step = match rng.range(0, 20) {
+                // too large, no change, wrong direction
+                0 => rng.range(4, 8) as i64 * sign,
+                1 => 0,
+                2 => -sign * rng.range(1, 4) as i64,
+                _ => sign * rng.range(1, 4) as i64,
+            };
+            if i + 1 < len {
+                level += step;
+            }
+        }
+
+        let line: Vec<String> = levels.iter().map(|l| l.to_string()).collect();
+        input.push_str(&line.join(" "));
+        input.push('\n');
+    }
+
+    input
+}
+
+/// Corrupted memory of about `bytes` bytes, with valid instructions,
+/// near misses and garbage, split into lines like the real input.
+pub fn day3(seed: u64, bytes: usize) -> String {
+    const GARBAGE: &[u8] =
+        b"()[]{}<>,'\"!?@#$%^&*-+=_/\\:; when select how what from who mul do don't";
+    const NEAR_MISSES: &[&str] = &[
+        "mul(1000,2)",
+        "mul( 1,2)",
+        "mul[3,7]",
+        "mul(4*",
+        "mul(6,9!",
+        "?(12,34)",
+        "mul ( 2 , 4 )",
+        "do_not_mul(5,5)",
+        "don't",
+        "do(",
+    ];
+
+    let mut rng = Rng::new(seed);
+    let mut input = String::with_capacity(bytes + 32);
+    let mut line_len = 0;
+
+    while input.len() < bytes {
+        let before = input.len();
+        match rng.range(0, 100) {
+            0..=11 => write!(input, "mul({},{})", rng.range(0, 1000), rng.range(0, 1000)).unwrap(),
+            12..=13 => input.push_str("do()"),
+            14..=15 => input.push_str("don't()"),
+            16..=19 => input.push_str(rng.pick::<&str>(NEAR_MISSES)),
+            _ => input.push(*rng.pick(GARBAGE) as char),
+        }
+
+        line_len += input.len() - before;
+        if line_len > 3000 {
+            input.push('\n');
+            line_len = 0;
+        }
+    }
+
+    if !input.ends_with('\n') {
+        input.push('\n');
+    }
+
+    input
+}
+
+/// Random `width` x `height` grid of `XMAS` letters.
+pub fn day4(seed: u64, width: usize, height: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut input = String::with_capacity((width + 1) * height);
+
+    for _ in 0..height {
+        for _ in 0..width {
+            input.push(*rng.pick(&['X', 'M', 'A', 'S']));
+        }
+        input.push('\n');
+    }
+
+    input
+}
+
+/// Rules ordering every pair of `pages` distinct pages, followed by `updates`
+/// updates of odd length, about half of them out of order.
+pub fn day5(seed: u64, pages: usize, updates: usize) -> String {
+    assert!(pages >= 3, "updates need at least 3 pages");
+    let mut rng = Rng::new(seed);
+    let mut input = String::new();
+
+    // distinct page numbers, the shuffled order is the one rules describe
+    let mut numbers: Vec<u64> = (10..10 + 2 * pages as u64).collect();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(pages);
+
+    let mut rules = Vec::with_capacity(pages * pages / 2);
+    for i in 0..numbers.len() {
+        for j in i + 1..numbers.len() {
+            rules.push((numbers[i], numbers[j]));
+        }
+    }
+    rng.shuffle(&mut rules);
+
+    for (before, after) in rules {
+        writeln!(input, "{}|{}", before, after).unwrap();
+    }
+
+    input.push('\n');
+
+    // longest odd update, so every update has a middle page
+    let max_len = pages.min(23) - (1 - pages.min(23) % 2);
+    for _ in 0..updates {
+        let len = rng.range(1, max_len as u64 / 2 + 1) as usize * 2 + 1;
+
+        let mut indices: Vec<usize> = (0..pages).collect();
+        rng.shuffle(&mut indices);
+        indices.truncate(len);
+        if rng.chance(0.5) {
+            indices.sort_unstable();
+        }
+
+        let update: Vec<String> = indices.iter().map(|&i| numbers[i].to_string()).collect();
+        input.push_str(&update.join(","));
+        input.push('\n');
+    }
+
+    input
+}
+
+/// Share of the cells the guard has to walk on in a generated map, less
+/// than in the real input but enough for the work to grow with the map.
+const MIN_COVERAGE: f64 = 0.1;
+
+/// Share of the cells the planned path covers before heading for the edge.
+const TARGET_COVERAGE: f64 = 0.2;
+
+/// `width` x `height` lab map with a guard facing up whose walk covers at
+/// least [`MIN_COVERAGE`] of the cells, and more obstacles on a `density`
+/// fraction of the cells off the walk. The path is planned first, with an
+/// obstacle put in front of the guard at every turn. Random maps only give
+/// walks of a few dozen cells.
+pub fn day6(seed: u64, width: usize, height: usize, density: f64) -> String {
+    const DELTAS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+    let mut rng = Rng::new(seed);
+    let cells = width * height;
+    let inside = |(x, y): (isize, isize)| {
+        (0..width as isize).contains(&x) && (0..height as isize).contains(&y)
+    };
+    let index = |(x, y): (isize, isize)| y as usize * width + x as usize;
+
+    loop {
+        let mut obstacles = vec![false; cells];
+        let mut path = vec![false; cells];
+        let start = (
+            rng.range(0, width as u64) as isize,
+            rng.range(0, height as u64) as isize,
+        );
+        let (mut pos, mut direction) = (start, 0);
+        path[index(start)] = true;
+        let mut covered = 1;
+
+        loop {
+            let (dx, dy) = DELTAS[direction];
+            let ahead = |(x, y): (isize, isize), n: isize| (x + dx * n, y + dy * n);
+            let mut run = 0;
+            while inside(ahead(pos, run + 1)) && !obstacles[index(ahead(pos, run + 1))] {
+                run += 1;
+            }
+
+            let leaving =
+                !inside(ahead(pos, run + 1)) && covered as f64 >= TARGET_COVERAGE * cells as f64;
+            let len = if leaving {
+                run
+            } else {
+                // turn on a new obstacle off the path, not facing the edge
+                let (rx, ry) = DELTAS[(direction + 1) % 4];
+                let mut lens: Vec<isize> = (0..=run).collect();
+                rng.shuffle(&mut lens);
+                let turn = lens.into_iter().find(|&len| {
+                    let obstacle = ahead(pos, len + 1);
+                    let (x, y) = ahead(pos, len);
+                    inside(obstacle)
+                        && !path[index(obstacle)]
+                        && !obstacles[index(obstacle)]
+                        && inside((x + rx, y + ry))
+                });
+                match turn {
+                    Some(len) => len,
+                    None => break,
+                }
+            };
+
+            for _ in 0..len {
+                pos = ahead(pos, 1);
+                if !path[index(pos)] {
+                    path[index(pos)] = true;
+                    covered += 1;
+                }
+            }
+            if leaving {
+                break;
+            }
+            obstacles[index(ahead(pos, 1))] = true;
+            direction = (direction + 1) % 4;
+        }
+
+        let mut input = String::with_capacity((width + 1) * height);
+        for i in 0..cells {
+            if i == index(start) {
+                input.push('^');
+            } else if obstacles[i] || (!path[i] && rng.chance(density)) {
+                input.push('#');
+            } else {
+                input.push('.');
+            }
+            if i % width == width - 1 {
+                input.push('\n');
+            }
+        }
+
+        // the walk can still loop or be short if planning got stuck
+        let map = day6::parse_input(&input).expect("generated map is valid");
+        if day6::part1(&map).is_ok_and(|visited| visited as f64 >= MIN_COVERAGE * cells as f64) {
+            return input;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gen::*;
+    use crate::solution::{Part, DAYS};
+
+    #[test]
+    fn test_deterministic() {
+        for day in 1..=6 {
+            assert_eq!(generate(day, 7, 2000), generate(day, 7, 2000));
+            assert_ne!(generate(day, 7, 2000), generate(day, 8, 2000));
+        }
+        assert_eq!(None, generate(25, 7, 2000));
+    }
+
+    #[test]
+    fn test_size() {
+        for day in 1..=6 {
+            let len = generate(day, 1, 20_000).unwrap().len();
+            assert!(
+                (10_000..40_000).contains(&len),
+                "day {} has {} bytes",
+                day,
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_day6_path() {
+        for seed in [0, 1, 2, 5, 6, 7, 2024] {
+            for bytes in [1_000, 16_000] {
+                let map = day6::parse_input(&generate(6, seed, bytes).unwrap()).unwrap();
+                let cells = map.width() * map.height();
+                let visited = day6::part1(&map).unwrap() as usize;
+                assert!(
+                    visited * 10 >= cells,
+                    "seed {} walks {} of {} cells",
+                    seed,
+                    visited,
+                    cells
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_solvable() {
+        for runner in DAYS {
+            let input = generate(runner.day(), 42, 3000).unwrap();
+
+            for part in Part::ALL {
+                for variant in
+                    std::iter::once(None).chain(runner.variants(part).into_iter().map(Some))
+                {
+                    assert!(
+                        runner.run(&input, part, variant).is_ok(),
+                        "day {} part {} {:?}",
+                        runner.day(),
+                        part.number(),
+                        variant
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::ops::Add;
+
+use crate::error::ParseError;
+
+/// Position in a grid. `x` is the column and `y` is the row, both counting
+/// from the top-left corner. Coordinates are signed so that offsets can step
+/// outside of the grid and be rejected by bounds checks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+/// Offsets of the 4 orthogonal neighbors, clockwise starting from up.
+pub const ORTHOGONAL: [Point; 4] = [
+    Point::new(0, -1),
+    Point::new(1, 0),
+    Point::new(0, 1),
+    Point::new(-1, 0),
+];
+
+/// Offsets of all 8 neighbors, clockwise starting from up.
+pub const ALL_DIRECTIONS: [Point; 8] = [
+    Point::new(0, -1),
+    Point::new(1, -1),
+    Point::new(1, 0),
+    Point::new(1, 1),
+    Point::new(0, 1),
+    Point::new(-1, 1),
+    Point::new(-1, 0),
+    Point::new(-1, -1),
+];
+
+/// Dense rectangular grid stored row by row.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from text, one row per line, mapping every character
+    /// with `f`.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        mut f: impl FnMut(char) -> T,
+    ) -> Result<Grid<T>, ParseError> {
+        Grid::try_parse(day, input, |c| Some(f(c)))
+    }
+
+    /// Like [`Grid::parse`], but `f` returns `None` for characters that
+    /// are not allowed in the grid.
+    pub fn try_parse(
+        day: u8,
+        input: &str,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for (y, line) in input.lines().enumerate() {
+            let mut len = 0;
+            for c in line.chars() {
+                let cell = f(c).ok_or_else(|| ParseError::UnexpectedToken {
+                    day,
+                    line: y + 1,
+                    column: len + 1,
+                    token: c.to_string(),
+                    expected: "a grid cell",
+                })?;
+                cells.push(cell);
+                len += 1;
+            }
+
+            match width {
+                None => width = Some(len),
+                Some(w) if len < w => {
+                    return Err(ParseError::UnexpectedEnd {
+                        day,
+                        line: y + 1,
+                        column: len + 1,
+                        expected: "a row as long as the first one",
+                    })
+                }
+                Some(w) if len > w => {
+                    return Err(ParseError::UnexpectedToken {
+                        day,
+                        line: y + 1,
+                        column: w + 1,
+                        token: line.chars().skip(w).collect(),
+                        expected: "end of row",
+                    })
+                }
+                _ => {}
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    fn index(&self, p: Point) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    fn point(&self, index: usize) -> Point {
+        Point::new((index % self.width) as i64, (index / self.width) as i64)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.index(p).map(|i| &mut self.cells[i])
+    }
+
+    /// Replace the cell at `p`, returning the previous value. Does nothing
+    /// and returns `None` if `p` is outside of the grid.
+    pub fn set(&mut self, p: Point, value: T) -> Option<T> {
+        self.get_mut(p).map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// All cells with their positions, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (self.point(i), cell))
+    }
+
+    /// Position of the first cell (in row-major order) matching `predicate`.
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Point> {
+        self.cells.iter().position(predicate).map(|i| self.point(i))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on 0, an empty grid has no rows anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    /// Cells visited walking from `start` (inclusive) by `step` until leaving
+    /// the grid. Use a diagonal step such as `(1, 1)` to walk diagonals.
+    pub fn ray(&self, start: Point, step: Point) -> impl Iterator<Item = (Point, &T)> {
+        let mut pos = start;
+        std::iter::from_fn(move || {
+            let cell = self.get(pos)?;
+            let current = pos;
+            pos = pos + step;
+            Some((current, cell))
+        })
+    }
+
+    /// Main diagonal (down-right) passing through `p`, from its top-left end.
+    pub fn diagonal(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        let back = p.x.min(p.y);
+        self.ray(Point::new(p.x - back, p.y - back), Point::new(1, 1))
+    }
+
+    /// Anti-diagonal (down-left) passing through `p`, from its top-right end.
+    pub fn anti_diagonal(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        let back = (self.width as i64 - 1 - p.x).min(p.y);
+        self.ray(Point::new(p.x + back, p.y - back), Point::new(-1, 1))
+    }
+
+    fn neighbors<'a>(
+        &'a self,
+        p: Point,
+        offsets: &'a [Point],
+    ) -> impl Iterator<Item = (Point, &'a T)> {
+        offsets
+            .iter()
+            .filter_map(move |&d| self.get(p + d).map(|cell| (p + d, cell)))
+    }
+
+    /// Orthogonal neighbors of `p` that are inside the grid.
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbors(p, &ORTHOGONAL)
+    }
+
+    /// Orthogonal and diagonal neighbors of `p` that are inside the grid.
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbors(p, &ALL_DIRECTIONS)
+    }
+
+    /// New grid of the same shape with every cell mapped by `f`.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Position of the first cell equal to `value`, e.g. a `^` marker.
+    pub fn find(&self, value: &T) -> Option<Point> {
+        self.position(|cell| cell == value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::*;
+
+    static INPUT: &str = "abc
+def
+ghi
+jkl";
+
+    fn chars(grid: &Grid<char>) -> String {
+        grid.rows()
+            .map(|row| row.iter().collect::<String>())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse(0, INPUT, |c| c).unwrap();
+
+        assert_eq!(3, grid.width());
+        assert_eq!(4, grid.height());
+        assert_eq!(Some(&'f'), grid.get(Point::new(2, 1)));
+        assert_eq!(None, grid.get(Point::new(3, 1)));
+        assert_eq!(None, grid.get(Point::new(-1, 0)));
+        assert_eq!(Some(Point::new(1, 3)), grid.find(&'k'));
+        assert_eq!(None, grid.find(&'z'));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err(ParseError::UnexpectedEnd {
+                day: 0,
+                line: 2,
+                column: 2,
+                expected: "a row as long as the first one",
+            }),
+            Grid::parse(0, "ab\nc", |c| c)
+        );
+        assert_eq!(
+            Err(ParseError::UnexpectedToken {
+                day: 0,
+                line: 1,
+                column: 2,
+                token: "x".to_string(),
+                expected: "a grid cell",
+            }),
+            Grid::try_parse(0, ".x", |c| (c == '.').then_some(c))
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = Grid::parse(0, INPUT, |c| c).unwrap();
+
+        assert_eq!(&['d', 'e', 'f'], grid.row(1));
+        assert_eq!("behk", grid.column(1).collect::<String>());
+        assert_eq!(
+            "aei",
+            grid.diagonal(Point::new(2, 2))
+                .map(|(_, c)| c)
+                .collect::<String>()
+        );
+        assert_eq!(
+            "ceg",
+            grid.anti_diagonal(Point::new(1, 1))
+                .map(|(_, c)| c)
+                .collect::<String>()
+        );
+        assert_eq!(
+            "jhf",
+            grid.ray(Point::new(0, 3), Point::new(1, -1))
+                .map(|(_, c)| c)
+                .collect::<String>()
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::parse(0, INPUT, |c| c).unwrap();
+
+        let corner: String = grid.neighbors4(Point::new(0, 0)).map(|(_, c)| c).collect();
+        assert_eq!("bd", corner);
+
+        let center: String = grid.neighbors8(Point::new(1, 1)).map(|(_, c)| c).collect();
+        assert_eq!("bcfihgda", center);
+    }
+
+    #[test]
+    fn test_set_and_map() {
+        let mut grid = Grid::parse(0, INPUT, |c| c).unwrap();
+
+        assert_eq!(Some('e'), grid.set(Point::new(1, 1), '#'));
+        assert_eq!(None, grid.set(Point::new(5, 5), '#'));
+        assert_eq!("abcd#fghijkl", chars(&grid));
+
+        let upper = grid.map(|c| c.to_ascii_uppercase());
+        assert_eq!("ABCD#FGHIJKL", chars(&upper));
+    }
+}
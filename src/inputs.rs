@@ -0,0 +1,198 @@
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Directory used when `AOC_INPUT_DIR` is not set, the one `cargo aoc input`
+/// downloads to.
+pub const DEFAULT_DIR: &str = "input/2024";
+
+/// Name of the puzzle input downloaded from the website, stored as
+/// `dayN.txt`. Every other name is stored as `dayN-name.txt`.
+pub const PERSONAL: &str = "personal";
+
+#[derive(Debug)]
+pub enum InputError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    /// Windows line ending on a 1-based line.
+    CarriageReturn {
+        path: PathBuf,
+        line: usize,
+    },
+    MissingTrailingNewline {
+        path: PathBuf,
+    },
+    /// Empty lines at the end of the file, starting at a 1-based line.
+    TrailingBlankLines {
+        path: PathBuf,
+        line: usize,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            InputError::CarriageReturn { path, line } => {
+                write!(f, "{}:{}: CRLF line ending", path.display(), line)
+            }
+            InputError::MissingTrailingNewline { path } => {
+                write!(f, "{}: missing newline at end of file", path.display())
+            }
+            InputError::TrailingBlankLines { path, line } => {
+                write!(f, "{}:{}: blank lines at end of file", path.display(), line)
+            }
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Directory of puzzle inputs, with any number of named inputs per day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Default for Inputs {
+    /// `AOC_INPUT_DIR`, or [`DEFAULT_DIR`] relative to the working directory.
+    fn default() -> Inputs {
+        match env::var_os("AOC_INPUT_DIR") {
+            Some(dir) => Inputs::new(dir),
+            None => Inputs::new(DEFAULT_DIR),
+        }
+    }
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Inputs {
+        Inputs { dir: dir.into() }
+    }
+
+    /// Inputs checked into this repository, independent of the working
+    /// directory.
+    pub fn fixtures() -> Inputs {
+        Inputs::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_DIR))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self, day: u8, name: &str) -> PathBuf {
+        if name == PERSONAL {
+            self.dir.join(format!("day{}.txt", day))
+        } else {
+            self.dir.join(format!("day{}-{}.txt", day, name))
+        }
+    }
+
+    /// Read and validate the input called `name` of `day`.
+    pub fn load(&self, day: u8, name: &str) -> Result<String, InputError> {
+        let path = self.path(day, name);
+        let text = fs::read_to_string(&path).map_err(|error| InputError::Io {
+            path: path.clone(),
+            error,
+        })?;
+
+        validate(&path, &text)?;
+
+        Ok(text)
+    }
+}
+
+/// Inputs must use `\n` line endings and end with exactly one newline, like
+/// the ones served by the website.
+pub fn validate(path: &Path, text: &str) -> Result<(), InputError> {
+    if let Some(i) = text.split('\n').position(|line| line.ends_with('\r')) {
+        return Err(InputError::CarriageReturn {
+            path: path.to_path_buf(),
+            line: i + 1,
+        });
+    }
+
+    if !text.is_empty() && !text.ends_with('\n') {
+        return Err(InputError::MissingTrailingNewline {
+            path: path.to_path_buf(),
+        });
+    }
+
+    let content = text.trim_end_matches('\n');
+    if text.len() - content.len() > 1 {
+        return Err(InputError::TrailingBlankLines {
+            path: path.to_path_buf(),
+            line: content.lines().count() + 1,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::answers::{self, Answers, Status};
+    use crate::inputs::*;
+    use crate::solution::{self, Part, DAYS};
+
+    #[test]
+    fn test_paths() {
+        let inputs = Inputs::new("inputs");
+
+        assert_eq!(Path::new("inputs/day3.txt"), inputs.path(3, PERSONAL));
+        assert_eq!(
+            Path::new("inputs/day3-example2.txt"),
+            inputs.path(3, "example2")
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let path = Path::new("day1.txt");
+
+        assert!(validate(path, "1 2\n3 4\n").is_ok());
+        assert!(validate(path, "").is_ok());
+        assert!(matches!(
+            validate(path, "1 2\r\n3 4\r\n"),
+            Err(InputError::CarriageReturn { line: 1, .. })
+        ));
+        assert!(matches!(
+            validate(path, "1 2\n3 4"),
+            Err(InputError::MissingTrailingNewline { .. })
+        ));
+        assert!(matches!(
+            validate(path, "1 2\n3 4\n\n\n"),
+            Err(InputError::TrailingBlankLines { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_examples() {
+        let inputs = Inputs::fixtures();
+        let text = fs::read_to_string(inputs.dir().join("answers-example.toml")).unwrap();
+        let answers = Answers::parse(&text).unwrap();
+
+        for runner in DAYS {
+            let input = inputs.load(runner.day(), "example").unwrap();
+
+            for check in answers::verify(*runner, Some(&input), &answers) {
+                assert_eq!(Status::Pass, check.status, "{:?}", check);
+            }
+        }
+
+        let example2 = inputs.load(3, "example2").unwrap();
+        let run = solution::day(3).unwrap().run(&example2, Part::Two, None);
+        assert_eq!(48, run.unwrap().answer);
+    }
+}
@@ -3,11 +3,18 @@ extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod answers;
 pub mod day1;
 pub mod day2;
 pub mod day3;
 pub mod day4;
 pub mod day5;
 pub mod day6;
+pub mod error;
+pub mod gen;
+pub mod grid;
+pub mod inputs;
+mod parse;
+pub mod solution;
 
 aoc_lib! { year = 2024 }
@@ -1,6 +1,675 @@
-extern crate aoc_runner;
-extern crate aoc_runner_derive;
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, IsTerminal, Read},
+    process::ExitCode,
+    time::Duration,
+};
 
-use aoc_runner_derive::aoc_main;
+use aoc2024::{
+    answers::{self, Answers, Status},
+    day2::{self, ReportVerdict, SafetyPolicy},
+    day3::{self, annotate::Kind},
+    day5::{self, Day5, DotOptions},
+    day6::{self, visualize::Player, visualize::View, Day6},
+    gen,
+    grid::Point,
+    inputs::{self, Inputs},
+    solution::{self, Part, Runner, Solution},
+};
 
-aoc_main! { lib = aoc2024 }
+const USAGE: &str =
+    "Usage: aoc2024 [--day N] [--part 1|2] [--variant NAME] [INPUT] [--all] [--threads N]
+       aoc2024 --verify [--day N] [INPUT] [--answers PATH]
+       aoc2024 --check [--day N] [--part 1|2] [INPUT]
+       aoc2024 --generate BYTES --day N [--seed SEED]
+       aoc2024 --dot [--update N] [INPUT]
+       aoc2024 --visualize [--guard N] [--obstacle X,Y] [--delay MS] [INPUT]
+       aoc2024 --annotate [--part 1|2] [--html] [INPUT]
+       aoc2024 --explain [--part 1|2] [POLICY] [INPUT]
+
+INPUT is either --input PATH|- or [--input-name NAME] [--input-dir DIR].
+POLICY is any of --min-step N, --max-step N, --plateaus, --direction-changes N
+and --tolerance N.
+
+Runs solutions without cargo-aoc. Without --day or --all the latest day is run.
+
+Options:
+    --day N          run only day N
+    --part 1|2       run only this part
+    --variant NAME   run only the NAME variant (e.g. heap, parallel), in the
+                     parts that have one unless --part is given
+    --input PATH     read input from PATH, or from stdin when PATH is `-`
+                     instead of the inputs directory
+    --input-name NAME
+                     named input to load, e.g. example (default: personal,
+                     which is dayN.txt, other names are dayN-NAME.txt)
+    --input-dir DIR  inputs directory (default: $AOC_INPUT_DIR or input/2024)
+    --all            run every day, part and variant
+    --threads N      threads of the day 6 parallel variant (default:
+                     $AOC_THREADS or one per core)
+    --verify         check every part and variant against the answers file
+    --answers PATH   answers file used by --verify (default: answers.toml)
+    --check          check that all variants of a part give the same answer
+    --generate BYTES print a generated input of about BYTES bytes for --day
+    --seed SEED      seed of the generated input (default: 0)
+    --dot            print the day 5 ordering rules as a Graphviz graph
+    --update N       with --dot, only the rules between pages of the Nth
+                     update, broken ones in red, labelled with the fixed order
+    --visualize      replay the day 6 guard's walk in the terminal, space
+                     pauses, n steps, + and - change the speed, q quits
+    --guard N        with --visualize, follow the Nth guard in reading order
+                     (default: 1)
+    --obstacle X,Y   with --visualize, put an extra obstacle on the 0-based
+                     column X and row Y
+    --delay MS       with --visualize, time between steps (default: 50)
+    --annotate       print the day 3 memory with the instructions found in it,
+                     the running sum after every counted multiplication and
+                     near misses such as mul(1000,2), with part 1 rules if
+                     --part 1
+    --html           with --annotate, print an HTML page instead
+    --explain        print whether every day 2 report is safe and why, with
+                     the Problem Dampener unless --part 1
+    --min-step N     with --explain, smallest step between levels (default: 1)
+    --max-step N     with --explain, largest step between levels (default: 3)
+    --plateaus       with --explain, allow two equal levels in a row
+    --direction-changes N
+                     with --explain, times a report may change direction
+                     (default: 0)
+    --tolerance N    with --explain, bad levels the Problem Dampener may
+                     remove (default: 0 with --part 1, else 1)
+    -h, --help       print this help";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Run,
+    Verify,
+    Check,
+    Generate(usize),
+    Dot,
+    Visualize,
+    Annotate,
+    Explain,
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    mode: Mode,
+    day: Option<u8>,
+    part: Option<Part>,
+    variant: Option<String>,
+    input: Option<String>,
+    input_name: Option<String>,
+    input_dir: Option<String>,
+    all: bool,
+    answers: Option<String>,
+    seed: u64,
+    update: Option<usize>,
+    guard: Option<usize>,
+    obstacle: Option<Point>,
+    delay: Option<u64>,
+    html: bool,
+    min_step: Option<u32>,
+    max_step: Option<u32>,
+    plateaus: bool,
+    direction_changes: Option<usize>,
+    tolerance: Option<usize>,
+    threads: Option<usize>,
+}
+
+impl Options {
+    /// Whether every day is selected, `--verify` and `--check` default to
+    /// all days.
+    fn all_days(&self) -> bool {
+        self.all || (matches!(self.mode, Mode::Verify | Mode::Check) && self.day.is_none())
+    }
+
+    /// Day 2 rules of `--part`, changed by the policy options.
+    fn policy(&self) -> SafetyPolicy {
+        let base = match self.part {
+            Some(Part::One) => SafetyPolicy::STRICT,
+            _ => SafetyPolicy::DAMPENED,
+        };
+
+        SafetyPolicy {
+            min_step: self.min_step.unwrap_or(base.min_step),
+            max_step: self.max_step.unwrap_or(base.max_step),
+            allow_plateaus: self.plateaus || base.allow_plateaus,
+            direction_changes: self.direction_changes.unwrap_or(base.direction_changes),
+            tolerance: self.tolerance.unwrap_or(base.tolerance),
+        }
+    }
+
+    fn has_policy(&self) -> bool {
+        self.min_step.is_some()
+            || self.max_step.is_some()
+            || self.plateaus
+            || self.direction_changes.is_some()
+            || self.tolerance.is_some()
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+        let mut set_mode = |mode: Mode| {
+            if options.mode != Mode::Run {
+                return Err(format!("{} can't be used with another mode", arg));
+            }
+            options.mode = mode;
+            Ok(())
+        };
+
+        match arg.as_str() {
+            "--day" => {
+                let day = value("--day")?;
+                options.day = Some(day.parse().map_err(|_| format!("invalid day `{}`", day))?);
+            }
+            "--part" => {
+                options.part = match value("--part")?.as_str() {
+                    "1" => Some(Part::One),
+                    "2" => Some(Part::Two),
+                    other => return Err(format!("invalid part `{}`", other)),
+                }
+            }
+            "--variant" => options.variant = Some(value("--variant")?),
+            "--input" => options.input = Some(value("--input")?),
+            "--input-name" => options.input_name = Some(value("--input-name")?),
+            "--input-dir" => options.input_dir = Some(value("--input-dir")?),
+            "--all" => options.all = true,
+            "--threads" => {
+                let threads = value("--threads")?;
+                options.threads = Some(
+                    threads
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid threads `{}`", threads))?,
+                );
+            }
+            "--verify" => set_mode(Mode::Verify)?,
+            "--check" => set_mode(Mode::Check)?,
+            "--generate" => {
+                let bytes = value("--generate")?;
+                set_mode(Mode::Generate(
+                    bytes
+                        .parse()
+                        .map_err(|_| format!("invalid size `{}`", bytes))?,
+                ))?;
+            }
+            "--dot" => set_mode(Mode::Dot)?,
+            "--update" => {
+                let update = value("--update")?;
+                options.update = Some(
+                    update
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid update `{}`", update))?,
+                );
+            }
+            "--visualize" => set_mode(Mode::Visualize)?,
+            "--guard" => {
+                let guard = value("--guard")?;
+                options.guard = Some(
+                    guard
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid guard `{}`", guard))?,
+                );
+            }
+            "--obstacle" => {
+                let obstacle = value("--obstacle")?;
+                options.obstacle = Some(
+                    obstacle
+                        .split_once(',')
+                        .and_then(|(x, y)| Some(Point::new(x.parse().ok()?, y.parse().ok()?)))
+                        .ok_or_else(|| format!("invalid obstacle `{}`", obstacle))?,
+                );
+            }
+            "--delay" => {
+                let delay = value("--delay")?;
+                options.delay = Some(
+                    delay
+                        .parse()
+                        .map_err(|_| format!("invalid delay `{}`", delay))?,
+                );
+            }
+            "--annotate" => set_mode(Mode::Annotate)?,
+            "--html" => options.html = true,
+            "--explain" => set_mode(Mode::Explain)?,
+            "--min-step" => {
+                let step = value("--min-step")?;
+                options.min_step = Some(
+                    step.parse()
+                        .map_err(|_| format!("invalid step `{}`", step))?,
+                );
+            }
+            "--max-step" => {
+                let step = value("--max-step")?;
+                options.max_step = Some(
+                    step.parse()
+                        .map_err(|_| format!("invalid step `{}`", step))?,
+                );
+            }
+            "--plateaus" => options.plateaus = true,
+            "--direction-changes" => {
+                let changes = value("--direction-changes")?;
+                options.direction_changes = Some(
+                    changes
+                        .parse()
+                        .map_err(|_| format!("invalid direction changes `{}`", changes))?,
+                );
+            }
+            "--tolerance" => {
+                let tolerance = value("--tolerance")?;
+                options.tolerance = Some(
+                    tolerance
+                        .parse()
+                        .map_err(|_| format!("invalid tolerance `{}`", tolerance))?,
+                );
+            }
+            "--seed" => {
+                let seed = value("--seed")?;
+                options.seed = seed
+                    .parse()
+                    .map_err(|_| format!("invalid seed `{}`", seed))?;
+            }
+            "--answers" => options.answers = Some(value("--answers")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            other => return Err(format!("unknown argument `{}`", other)),
+        }
+    }
+
+    if options.all && options.day.is_some() {
+        return Err("--all and --day can't be used together".to_string());
+    }
+    if matches!(options.mode, Mode::Generate(_)) && options.day.is_none() {
+        return Err("--generate needs --day".to_string());
+    }
+    if options.mode == Mode::Dot && options.day.is_some_and(|day| day != 5) {
+        return Err("--dot only works for day 5".to_string());
+    }
+    if options.mode == Mode::Visualize && options.day.is_some_and(|day| day != 6) {
+        return Err("--visualize only works for day 6".to_string());
+    }
+    if options.mode == Mode::Annotate && options.day.is_some_and(|day| day != 3) {
+        return Err("--annotate only works for day 3".to_string());
+    }
+    if options.mode == Mode::Explain && options.day.is_some_and(|day| day != 2) {
+        return Err("--explain only works for day 2".to_string());
+    }
+    if (options.guard.is_some() || options.obstacle.is_some() || options.delay.is_some())
+        && options.mode != Mode::Visualize
+    {
+        return Err("--guard, --obstacle and --delay need --visualize".to_string());
+    }
+    if options.html && options.mode != Mode::Annotate {
+        return Err("--html needs --annotate".to_string());
+    }
+    if options.update.is_some() && options.mode != Mode::Dot {
+        return Err("--update needs --dot".to_string());
+    }
+    if options.has_policy() && options.mode != Mode::Explain {
+        return Err("policy options need --explain".to_string());
+    }
+    if options.input.is_some() && (options.input_name.is_some() || options.input_dir.is_some()) {
+        return Err("--input can't be used with --input-name or --input-dir".to_string());
+    }
+    if options.input.is_some() && options.all_days() {
+        return Err("--input needs a single day".to_string());
+    }
+
+    Ok(options)
+}
+
+/// Input of `day`: the `--input` file as is, or the validated named input
+/// from the inputs directory.
+fn read_input(day: u8, options: &Options) -> Result<String, Box<dyn Error>> {
+    match options.input.as_deref() {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        Some(path) => Ok(fs::read_to_string(path)?),
+        None => {
+            let inputs = match &options.input_dir {
+                Some(dir) => Inputs::new(dir),
+                None => Inputs::default(),
+            };
+            let name = options.input_name.as_deref().unwrap_or(inputs::PERSONAL);
+            Ok(inputs.load(day, name)?)
+        }
+    }
+}
+
+/// Run the selected parts and variants of one day, returning whether all of
+/// them succeeded.
+fn run_day(runner: &dyn Runner, input: &str, options: &Options) -> bool {
+    let runs = match solution::select(runner, options.part, options.variant.as_deref()) {
+        Ok(runs) => runs,
+        Err(err) => {
+            println!("Day {} : FAILED\n\t{}\n", runner.day(), err);
+            return false;
+        }
+    };
+
+    let mut ok = true;
+    for (part, variant) in runs {
+        let title = match variant {
+            Some(name) => format!("Day {} - Part {} - {}", runner.day(), part.number(), name),
+            None => format!("Day {} - Part {}", runner.day(), part.number()),
+        };
+
+        match runner.run(input, part, variant) {
+            Ok(run) => println!(
+                "{} : {}\n\tgenerator: {:?},\n\trunner: {:?}\n",
+                title, run.answer, run.generator, run.runner
+            ),
+            Err(err) => {
+                println!("{} : FAILED\n\t{}\n", title, err);
+                ok = false;
+            }
+        }
+    }
+
+    ok
+}
+
+/// Check the selected days against the answers file, returning whether no
+/// answer was wrong.
+fn verify(runners: &[&dyn Runner], options: &Options) -> Result<bool, String> {
+    let path = options.answers.as_deref().unwrap_or("answers.toml");
+    let text = fs::read_to_string(path).map_err(|err| format!("can't read {}: {}", path, err))?;
+    let answers = Answers::parse(&text).map_err(|err| format!("{}: {}", path, err))?;
+
+    println!(
+        "{:<4} {:<5} {:<12} {:>16} {:>16}  Status",
+        "Day", "Part", "Variant", "Expected", "Actual"
+    );
+
+    let mut ok = true;
+    for runner in runners {
+        let input = match read_input(runner.day(), options) {
+            Ok(input) => Some(input),
+            Err(err) => {
+                eprintln!("can't read input for day {}: {}", runner.day(), err);
+                None
+            }
+        };
+
+        for check in answers::verify(*runner, input.as_deref(), &answers) {
+            let expected = check.expected.map_or("-".to_string(), |a| a.to_string());
+            let actual = match &check.actual {
+                Ok(answer) => answer.to_string(),
+                Err(_) => "-".to_string(),
+            };
+
+            println!(
+                "{:<4} {:<5} {:<12} {:>16} {:>16}  {}",
+                check.day,
+                check.part.number(),
+                check.variant.unwrap_or("-"),
+                expected,
+                actual,
+                check.status
+            );
+            if let (Status::Fail, Err(err)) = (&check.status, &check.actual) {
+                println!("\t{}", err);
+            }
+
+            ok &= check.status != Status::Fail;
+        }
+    }
+
+    Ok(ok)
+}
+
+/// Run all variants of the selected parts on the same input, returning
+/// whether they all agreed.
+fn check(runners: &[&dyn Runner], options: &Options) -> bool {
+    let mut ok = true;
+
+    for runner in runners {
+        let input = match read_input(runner.day(), options) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("can't read input for day {}: {}", runner.day(), err);
+                ok = false;
+                continue;
+            }
+        };
+
+        for part in Part::ALL {
+            if options.part.is_some_and(|p| p != part) {
+                continue;
+            }
+
+            let consistency = match solution::check_consistency(*runner, &input, part) {
+                Ok(consistency) => consistency,
+                Err(err) => {
+                    println!(
+                        "Day {} - Part {} : FAILED\n\t{}\n",
+                        runner.day(),
+                        part.number(),
+                        err
+                    );
+                    ok = false;
+                    continue;
+                }
+            };
+
+            let status = if consistency.is_consistent() {
+                "consistent"
+            } else {
+                "MISMATCH"
+            };
+            println!("Day {} - Part {} : {}", runner.day(), part.number(), status);
+            for (variant, answer) in &consistency.answers {
+                let answer = match answer {
+                    Ok(answer) => answer.to_string(),
+                    Err(err) => format!("FAILED: {}", err),
+                };
+                println!("\t{}: {}", variant.unwrap_or("(default)"), answer);
+            }
+            println!();
+
+            ok &= consistency.is_consistent();
+        }
+    }
+
+    ok
+}
+
+/// Print the day 5 rules as a Graphviz graph, optionally only those of one
+/// update.
+fn dot(options: &Options) -> Result<bool, String> {
+    let input = read_input(5, options).map_err(|err| format!("can't read input: {}", err))?;
+    let input = Day5::parse(&input).map_err(|err| err.to_string())?;
+
+    let update = match options.update {
+        Some(n) => Some(
+            input
+                .updates()
+                .get(n - 1)
+                .ok_or_else(|| format!("there are only {} updates", input.updates().len()))?
+                .as_slice(),
+        ),
+        None => None,
+    };
+
+    // both only mean something for a single update
+    let dot_options = DotOptions {
+        update,
+        highlight_violations: update.is_some(),
+        annotate_fix: update.is_some(),
+    };
+    print!("{}", day5::to_dot(input.graph(), &dot_options));
+
+    Ok(true)
+}
+
+/// Replay the day 6 guard's walk, or print where it ends when the output is
+/// not a terminal.
+fn visualize(options: &Options) -> Result<bool, String> {
+    let input = read_input(6, options).map_err(|err| format!("can't read input: {}", err))?;
+    let input = Day6::parse(&input).map_err(|err| err.to_string())?;
+
+    let delay = Duration::from_millis(options.delay.unwrap_or(50));
+    let n = options.guard.unwrap_or(1);
+    let guard = *input
+        .guards()
+        .get(n - 1)
+        .ok_or_else(|| format!("there are only {} guards", input.guards().len()))?;
+    let mut player = Player::new(&input, guard, options.obstacle, delay);
+
+    let mut stdout = io::stdout();
+    if stdout.is_terminal() {
+        player.play(&mut stdout).map_err(|err| err.to_string())?;
+    } else {
+        player.finish();
+        let (width, height) = (input.width(), input.height());
+        print!(
+            "{}",
+            player.frame(
+                View::around(Point::new(0, 0), (width, height), width, height),
+                false
+            )
+        );
+    }
+    println!("{}", day6::trace(&input, guard, options.obstacle));
+
+    Ok(true)
+}
+
+/// Print the day 3 memory with the instructions found in it highlighted.
+fn annotate(options: &Options) -> Result<bool, String> {
+    let input = read_input(3, options).map_err(|err| format!("can't read input: {}", err))?;
+    let conditionals = options.part != Some(Part::One);
+    let annotations = day3::annotate(&input, conditionals);
+
+    if options.html {
+        print!("{}", day3::annotate::to_html(&input, &annotations));
+    } else {
+        let color = io::stdout().is_terminal();
+        print!("{}", day3::annotate::to_text(&input, &annotations, color));
+        let near_misses = annotations
+            .iter()
+            .filter(|annotation| annotation.kind == Kind::NearMiss)
+            .count();
+        println!(
+            "\nsum: {}, {} near misses",
+            day3::annotate::total(&annotations),
+            near_misses
+        );
+    }
+
+    Ok(true)
+}
+
+/// Print the verdict of every day 2 report, one per line.
+fn explain(options: &Options) -> Result<bool, String> {
+    let input = read_input(2, options).map_err(|err| format!("can't read input: {}", err))?;
+    let reports = day2::parse_input(&input).map_err(|err| err.to_string())?;
+    let policy = options.policy();
+
+    for report in reports.iter() {
+        let verdict = ReportVerdict::dampened(report, &policy);
+        let levels: Vec<String> = report.iter().map(|level| level.to_string()).collect();
+        println!("{}: {}", levels.join(" "), verdict);
+    }
+
+    Ok(true)
+}
+
+/// Print the generated input of `--day`.
+fn generate(bytes: usize, options: &Options) -> Result<bool, String> {
+    let day = options.day.expect("--generate has a day");
+    let input = gen::generate(day, options.seed, bytes)
+        .ok_or_else(|| format!("no generator for day {}", day))?;
+    print!("{}", input);
+
+    Ok(true)
+}
+
+/// Run the selected days like cargo-aoc does.
+fn run(runners: &[&dyn Runner], options: &Options) -> Result<bool, String> {
+    println!("AOC 2024");
+
+    let mut ok = true;
+    for runner in runners {
+        let input = match read_input(runner.day(), options) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("can't read input for day {}: {}", runner.day(), err);
+                ok = false;
+                continue;
+            }
+        };
+
+        ok &= run_day(*runner, &input, options);
+    }
+
+    Ok(ok)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(threads) = options.threads {
+        day6::set_threads(threads);
+    }
+
+    let runners: Vec<&dyn Runner> = if options.all_days() {
+        solution::DAYS.to_vec()
+    } else {
+        let day = options
+            .day
+            .unwrap_or_else(|| solution::DAYS.last().expect("a solved day").day());
+        match solution::day(day) {
+            Ok(runner) => vec![runner],
+            Err(err) => {
+                eprintln!("{}", err);
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+
+    let result = match options.mode {
+        Mode::Run => run(&runners, &options),
+        Mode::Verify => verify(&runners, &options),
+        Mode::Check => Ok(check(&runners, &options)),
+        Mode::Generate(bytes) => generate(bytes, &options),
+        Mode::Dot => dot(&options),
+        Mode::Visualize => visualize(&options),
+        Mode::Annotate => annotate(&options),
+        Mode::Explain => explain(&options),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Tokenizers keeping the column of every token, so parse errors can point
+//! at it.
+
+/// Tokens of `line` separated by `separator`, with their 1-based column.
+pub(crate) fn fields(line: &str, separator: char) -> impl Iterator<Item = (usize, &str)> {
+    let mut column = 1;
+    line.split(separator).map(move |token| {
+        let start = column;
+        column += token.len() + separator.len_utf8();
+        (start, token)
+    })
+}
+
+/// ASCII whitespace separated tokens of `line`, with their 1-based column,
+/// found byte by byte.
+pub(crate) fn words(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let bytes = line.as_bytes();
+    let mut at = 0;
+
+    std::iter::from_fn(move || {
+        while bytes.get(at).is_some_and(u8::is_ascii_whitespace) {
+            at += 1;
+        }
+        if at == bytes.len() {
+            return None;
+        }
+
+        let start = at;
+        while bytes.get(at).is_some_and(|b| !b.is_ascii_whitespace()) {
+            at += 1;
+        }
+        Some((start + 1, &line[start..at]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::*;
+
+    #[test]
+    fn test_columns() {
+        assert_eq!(
+            vec![(1, "1"), (3, "22"), (6, ""), (7, "4")],
+            fields("1,22,,4", ',').collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(3, "12"), (8, "5")],
+            words("  12 \t 5").collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, "é1"), (6, "x")],
+            words("é1\r\nx\r").collect::<Vec<_>>()
+        );
+        assert_eq!(None, words(" \t ").next());
+    }
+}
@@ -0,0 +1,319 @@
+use std::time::{Duration, Instant};
+
+use crate::error::{ParseError, SolveError};
+use crate::{day1, day2, day3, day4, day5, day6};
+
+pub type Answer = u64;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+/// Alternative implementation of a part, the equivalent of
+/// `#[aoc(dayN, partN, name)]`.
+pub struct Variant<I> {
+    pub part: Part,
+    pub name: &'static str,
+    pub run: fn(&I) -> Result<Answer, SolveError>,
+}
+
+/// Puzzle of one day: a generator and both parts over its output.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError>;
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError>;
+
+    fn variants() -> Vec<Variant<Self::Input>> {
+        Vec::new()
+    }
+}
+
+/// Answer of a single run with the time spent in the generator and in the
+/// part itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub answer: Answer,
+    pub generator: Duration,
+    pub runner: Duration,
+}
+
+/// Outcome of a part (`None`) and each of its named variants.
+pub type VariantAnswers = Vec<(Option<&'static str>, Result<Answer, SolveError>)>;
+
+/// Object safe view of a [`Solution`], so days with different input types
+/// can live in one registry.
+pub trait Runner: Sync {
+    fn day(&self) -> u8;
+
+    /// Names of the alternative implementations of `part`.
+    fn variants(&self, part: Part) -> Vec<&'static str>;
+
+    /// Parse `input` and run `part`, or its variant called `variant`.
+    fn run(&self, input: &str, part: Part, variant: Option<&str>) -> Result<Run, SolveError>;
+
+    /// Parse `input` once and run `part` followed by all of its variants on
+    /// the same parsed input.
+    fn run_all(&self, input: &str, part: Part) -> Result<VariantAnswers, SolveError>;
+}
+
+impl<S: Solution + Sync> Runner for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn variants(&self, part: Part) -> Vec<&'static str> {
+        S::variants()
+            .into_iter()
+            .filter(|v| v.part == part)
+            .map(|v| v.name)
+            .collect()
+    }
+
+    fn run(&self, input: &str, part: Part, variant: Option<&str>) -> Result<Run, SolveError> {
+        let run: fn(&S::Input) -> Result<Answer, SolveError> = match (variant, part) {
+            (None, Part::One) => S::part1,
+            (None, Part::Two) => S::part2,
+            (Some(name), _) => {
+                S::variants()
+                    .into_iter()
+                    .find(|v| v.part == part && v.name == name)
+                    .ok_or_else(|| SolveError::UnknownVariant {
+                        day: S::DAY,
+                        part: Some(part.number()),
+                        name: name.to_string(),
+                    })?
+                    .run
+            }
+        };
+
+        let start = Instant::now();
+        let parsed = S::parse(input)?;
+        let generator = start.elapsed();
+
+        let start = Instant::now();
+        let answer = run(&parsed)?;
+        let runner = start.elapsed();
+
+        Ok(Run {
+            answer,
+            generator,
+            runner,
+        })
+    }
+
+    fn run_all(&self, input: &str, part: Part) -> Result<VariantAnswers, SolveError> {
+        let parsed = S::parse(input)?;
+
+        let default = match part {
+            Part::One => S::part1(&parsed),
+            Part::Two => S::part2(&parsed),
+        };
+        let variants = S::variants()
+            .into_iter()
+            .filter(|v| v.part == part)
+            .map(|v| (Some(v.name), (v.run)(&parsed)));
+
+        Ok(std::iter::once((None, default)).chain(variants).collect())
+    }
+}
+
+/// Answers of all variants of a part on the same input.
+#[derive(Debug)]
+pub struct Consistency {
+    pub day: u8,
+    pub part: Part,
+    pub answers: VariantAnswers,
+}
+
+impl Consistency {
+    /// Whether every variant succeeded with the same answer.
+    pub fn is_consistent(&self) -> bool {
+        match self.answers.first() {
+            Some((_, Ok(first))) => self
+                .answers
+                .iter()
+                .all(|(_, answer)| answer.as_ref() == Ok(first)),
+            _ => false,
+        }
+    }
+}
+
+/// Run every variant of `part` on `input` so they can be compared.
+pub fn check_consistency(
+    runner: &dyn Runner,
+    input: &str,
+    part: Part,
+) -> Result<Consistency, SolveError> {
+    Ok(Consistency {
+        day: runner.day(),
+        part,
+        answers: runner.run_all(input, part)?,
+    })
+}
+
+/// Parts and variants of `runner` to run when asked for `part` and
+/// `variant`, `None` meaning all of them. A variant asked for without a part
+/// only runs in the parts that have it.
+pub fn select<'a>(
+    runner: &dyn Runner,
+    part: Option<Part>,
+    variant: Option<&'a str>,
+) -> Result<Vec<(Part, Option<&'a str>)>, SolveError> {
+    let parts = Part::ALL
+        .into_iter()
+        .filter(|&p| part.is_none_or(|part| part == p));
+
+    match (part, variant) {
+        (_, None) => Ok(parts
+            .flat_map(|part| {
+                std::iter::once((part, None)).chain(
+                    runner
+                        .variants(part)
+                        .into_iter()
+                        .map(move |v| (part, Some(v))),
+                )
+            })
+            .collect()),
+        // unknown variants of a chosen part fail when run
+        (Some(part), Some(name)) => Ok(vec![(part, Some(name))]),
+        (None, Some(name)) => {
+            let runs: Vec<_> = parts
+                .filter(|&part| runner.variants(part).contains(&name))
+                .map(|part| (part, Some(name)))
+                .collect();
+            if runs.is_empty() {
+                return Err(SolveError::UnknownVariant {
+                    day: runner.day(),
+                    part: None,
+                    name: name.to_string(),
+                });
+            }
+            Ok(runs)
+        }
+    }
+}
+
+/// Every solved day, in order.
+pub static DAYS: &[&dyn Runner] = &[
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+];
+
+pub fn day(day: u8) -> Result<&'static dyn Runner, SolveError> {
+    DAYS.iter()
+        .find(|runner| runner.day() == day)
+        .copied()
+        .ok_or(SolveError::UnknownDay(day))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inputs::Inputs;
+    use crate::solution::*;
+
+    #[test]
+    fn test_registry() {
+        let days: Vec<u8> = DAYS.iter().map(|runner| runner.day()).collect();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], days);
+
+        assert_eq!(vec!["heap", "radix"], day(1).unwrap().variants(Part::One));
+        assert_eq!(
+            vec!["merge", "frequency"],
+            day(1).unwrap().variants(Part::Two)
+        );
+        assert_eq!(
+            vec!["brute_force", "parallel"],
+            day(6).unwrap().variants(Part::Two)
+        );
+        assert!(day(7).is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let input = &Inputs::fixtures().load(1, "example").unwrap();
+        let day1 = day(1).unwrap();
+
+        assert_eq!(11, day1.run(input, Part::One, None).unwrap().answer);
+        assert_eq!(11, day1.run(input, Part::One, Some("heap")).unwrap().answer);
+        assert_eq!(31, day1.run(input, Part::Two, None).unwrap().answer);
+
+        let consistency = check_consistency(day1, input, Part::One).unwrap();
+        assert!(consistency.is_consistent());
+        assert_eq!(
+            vec![
+                (None, Ok(11)),
+                (Some("heap"), Ok(11)),
+                (Some("radix"), Ok(11))
+            ],
+            consistency.answers
+        );
+
+        assert_eq!(
+            Err(SolveError::UnknownVariant {
+                day: 1,
+                part: Some(2),
+                name: "heap".to_string()
+            }),
+            day1.run(input, Part::Two, Some("heap"))
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        let day1 = day(1).unwrap();
+
+        assert_eq!(
+            vec![(Part::One, Some("heap"))],
+            select(day1, None, Some("heap")).unwrap()
+        );
+        assert_eq!(
+            vec![(Part::Two, Some("parallel"))],
+            select(day(6).unwrap(), None, Some("parallel")).unwrap()
+        );
+        assert_eq!(
+            vec![(Part::Two, Some("heap"))],
+            select(day1, Some(Part::Two), Some("heap")).unwrap()
+        );
+        assert_eq!(
+            Err(SolveError::UnknownVariant {
+                day: 1,
+                part: None,
+                name: "nope".to_string()
+            }),
+            select(day1, None, Some("nope"))
+        );
+
+        assert_eq!(
+            vec![
+                (Part::Two, None),
+                (Part::Two, Some("merge")),
+                (Part::Two, Some("frequency"))
+            ],
+            select(day1, Some(Part::Two), None).unwrap()
+        );
+        assert_eq!(6, select(day1, None, None).unwrap().len());
+    }
+}